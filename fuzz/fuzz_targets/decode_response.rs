@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[path = "../../src/decode.rs"]
+mod decode;
+
+//  Any bytes from the service must decode to a value or an error.
+fuzz_target!(|data : &[u8]| {
+  decode::all(data);
+});
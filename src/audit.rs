@@ -0,0 +1,345 @@
+use pgrx::*;
+use pgrx::prelude::*;
+use pgrx::bgworkers::*;
+use pgrx::lwlock::PgLwLock;
+use pgrx::shmem::*;
+use serde_json::json;
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::guc;
+
+//  ================================================================
+//
+//    Audit log of graph mutations
+//
+//  ================================================================
+
+extension_sql!(r#"
+CREATE TABLE IF NOT EXISTS mr_audit_log (
+  id               bigserial   PRIMARY KEY,
+  logged_at        timestamptz NOT NULL DEFAULT clock_timestamp(),
+  username         name        NOT NULL DEFAULT session_user,
+  application_name text        NOT NULL DEFAULT current_setting('application_name'),
+  xid              xid8        NOT NULL DEFAULT pg_current_xact_id(),
+  command          text        NOT NULL,
+  context          text        NOT NULL,
+  arguments        jsonb       NOT NULL,
+  outcome          text        NOT NULL
+);
+
+SELECT pg_catalog.pg_extension_config_dump('mr_audit_log', '');
+SELECT pg_catalog.pg_extension_config_dump('mr_audit_log_id_seq', '');
+"#,
+  name = "audit_log",
+);
+
+//  ================================================================
+//
+//    Queue
+//
+//  ================================================================
+//
+//  Mutations are applied by the service whether or not the calling
+//  transaction commits, so entries should outlive it too. With
+//  `shared_preload_libraries`, entries are put into a queue in shared
+//  memory and a background worker inserts them in its own transaction.
+//  Without it, or when the queue is full, they are inserted in the
+//  calling transaction and failures also go to the server log.
+
+const QUEUE_LEN : usize = 256;
+const ENTRY_LEN : usize = 2048;
+
+//  Advisory lock class of queue flushes, "au".
+const FLUSH_LOCK_CLASS : i32 = 0x6175;
+
+#[derive(Copy, Clone)]
+pub struct Entry {
+  pub len  : usize,
+  //  The row as JSON.
+  pub data : [u8; ENTRY_LEN],
+}
+
+#[derive(Copy, Clone)]
+pub struct Queue {
+  pub head    : u64,
+  pub tail    : u64,
+  pub entries : [Entry; QUEUE_LEN],
+}
+
+impl Default for Queue {
+  fn default() -> Queue {
+    Queue {
+      head    : 0,
+      tail    : 0,
+      entries : [Entry { len : 0, data : [0; ENTRY_LEN] }; QUEUE_LEN],
+    }
+  }
+}
+
+unsafe impl PGRXSharedMemory for Queue {}
+
+pub static QUEUE : PgLwLock<Queue> = PgLwLock::new();
+
+static ENABLED : AtomicBool = AtomicBool::new(false);
+
+pub fn init() {
+  if unsafe { !pg_sys::process_shared_preload_libraries_in_progress } {
+    return;
+  }
+  pg_shmem_init!(QUEUE);
+  ENABLED.store(true, Ordering::Relaxed);
+
+  BackgroundWorkerBuilder::new("pgmer2 audit")
+    .set_function("mr_audit_worker_main")
+    .set_library("pgmer2")
+    .enable_spi_access()
+    .set_restart_time(Some(Duration::from_secs(10)))
+    .load();
+}
+
+fn enabled() -> bool {
+  ENABLED.load(Ordering::Relaxed)
+}
+
+//  Returns false when the entry has to be written some other way.
+fn enqueue(row : &serde_json::Value) -> bool {
+  if !enabled() {
+    return false;
+  }
+
+  let data = row.to_string().into_bytes();
+  if data.len() > ENTRY_LEN {
+    return false;
+  }
+
+  let mut queue = QUEUE.exclusive();
+  if queue.tail - queue.head >= QUEUE_LEN as u64 {
+    return false;
+  }
+
+  let n                  = (queue.tail % QUEUE_LEN as u64) as usize;
+  queue.entries[n].len   = data.len();
+  queue.entries[n].data[..data.len()].copy_from_slice(&data);
+  queue.tail            += 1;
+  return true;
+}
+
+//  Inserts queued entries in the current transaction. Returns the
+//  number of entries inserted. Flushes are serialized, so an entry
+//  is only inserted once.
+pub fn flush() -> Result<usize, Box<dyn Error + 'static>> {
+  if !enabled() {
+    return Ok(0);
+  }
+
+  //  The worker's database may not have the extension.
+  if !Spi::get_one::<bool>("SELECT to_regclass('mr_audit_log') IS NOT NULL")?.unwrap_or(false) {
+    return Ok(0);
+  }
+
+  Spi::run_with_args(
+    "SELECT pg_advisory_xact_lock($1, 0)",
+    Some(vec![(PgBuiltInOids::INT4OID.oid(), FLUSH_LOCK_CLASS.into_datum())]),
+  )?;
+
+  let (head, rows) = {
+    let queue = QUEUE.share();
+    let rows : Vec<String> =
+      (queue.head..queue.tail)
+        .map(|n| &queue.entries[(n % QUEUE_LEN as u64) as usize])
+        .map(|x| String::from_utf8_lossy(&x.data[..x.len]).into_owned())
+        .collect();
+    (queue.head, rows)
+  };
+
+  for row in rows.iter() {
+    Spi::run_with_args(r#"
+      INSERT INTO mr_audit_log (logged_at, username, application_name, xid, command, context, arguments, outcome)
+      SELECT (x->>'logged_at')::timestamptz, x->>'username', x->>'application_name', (x->>'xid')::xid8,
+             x->>'command', x->>'context', x->'arguments', x->>'outcome'
+        FROM (SELECT $1::jsonb AS x) AS entry
+    "#,
+      Some(vec![(PgBuiltInOids::TEXTOID.oid(), row.as_str().into_datum())]),
+    )?;
+  }
+
+  QUEUE.exclusive().head = head + rows.len() as u64;
+  return Ok(rows.len());
+}
+
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn mr_audit_worker_main(_arg : pg_sys::Datum) {
+  BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGHUP | SignalWakeFlags::SIGTERM);
+  BackgroundWorker::connect_worker_to_spi(guc::string(&guc::WORKER_DATABASE).as_deref().or(Some("postgres")), None);
+
+  while BackgroundWorker::wait_latch(Some(Duration::from_secs(1))) {
+    let result = BackgroundWorker::transaction(|| flush());
+
+    if let Err(e) = result {
+      warning!("audit: {}", e);
+    }
+  }
+}
+
+//  ================================================================
+//
+//    Writing entries
+//
+//  ================================================================
+
+//  Nobody but the owner of the table can write it, so entries can't
+//  be forged. The connector inserts them with the privileges of the
+//  owner, and the username and transaction id always come from the
+//  column defaults.
+fn write(
+  command   : &str,
+  context   : &str,
+  arguments : serde_json::Value,
+  outcome   : &str,
+) -> Result<(), Box<dyn Error + 'static>> {
+  let owner = Spi::get_one::<pg_sys::Oid>(
+    "SELECT relowner FROM pg_catalog.pg_class WHERE oid = 'mr_audit_log'::regclass",
+  )?.ok_or("mr_audit_log not found")?;
+
+  crate::materialize::as_role(owner, || {
+    Spi::run_with_args(
+      "INSERT INTO mr_audit_log (command, context, arguments, outcome) VALUES ($1, $2, $3, $4)",
+      Some(vec![
+        (PgBuiltInOids::TEXTOID.oid(),  command.into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(),  context.into_datum()),
+        (PgBuiltInOids::JSONBOID.oid(), JsonB(arguments).into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(),  outcome.into_datum()),
+      ]),
+    )?;
+    Ok(())
+  })
+}
+
+//  Runs `f` and records the call with its outcome when
+//  `meritrank.audit` is on. Arguments are only built when the audit
+//  is enabled.
+pub fn audited<T>(
+  command   : &str,
+  context   : &str,
+  arguments : impl FnOnce() -> serde_json::Value,
+  f         : impl FnOnce() -> Result<T, Box<dyn Error + 'static>>,
+) -> Result<T, Box<dyn Error + 'static>> {
+  if !guc::AUDIT.get() {
+    return f();
+  }
+
+  let result    = f();
+  let arguments = arguments();
+  let outcome   = match &result {
+    Ok(_)  => "ok".to_string(),
+    Err(e) => format!("error: {}", e),
+  };
+
+  let (logged_at, username, application_name, xid) = Spi::get_four::<String, String, String, String>(
+    "SELECT clock_timestamp()::text, session_user::text, current_setting('application_name'), pg_current_xact_id()::text"
+  )?;
+
+  let row = json!({
+    "logged_at"        : logged_at,
+    "username"         : username,
+    "application_name" : application_name,
+    "xid"              : xid,
+    "command"          : command,
+    "context"          : context,
+    "arguments"        : arguments.clone(),
+    "outcome"          : outcome,
+  });
+
+  if enqueue(&row) {
+    return result;
+  }
+
+  //  A failed call aborts the transaction, and the entry with it.
+  if result.is_err() {
+    log!("meritrank: audit: {}", row);
+  }
+
+  write(command, context, arguments, &outcome)?;
+  return result;
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use crate::testing::Scope;
+
+  #[pg_test]
+  fn audit_disabled() {
+    let t = Scope::new();
+
+    let _ = crate::mr_put_edge(Some(t.node("U1").as_str()), Some(t.node("U2").as_str()), Some(1.0), None, None, None).unwrap();
+
+    let n = Spi::get_one::<i64>("SELECT count(*) FROM mr_audit_log WHERE xid = pg_current_xact_id()").unwrap().unwrap();
+    assert_eq!(n, 0);
+  }
+
+  #[pg_test]
+  fn audit_mutations() {
+    let t = Scope::new();
+    let x = t.context("X");
+    let (u1, u2) = (t.node("U1"), t.node("U2"));
+
+    Spi::run("SET meritrank.audit = on").unwrap();
+
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u2.as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_delete_edge(Some(u1.as_str()), Some(u2.as_str()), Some(x.as_str()), None).unwrap();
+    let _ = crate::mr_delete_node(Some(u1.as_str()), Some(x.as_str()), None).unwrap();
+
+    Spi::run("RESET meritrank.audit").unwrap();
+
+    //  The worker may have inserted some of them already.
+    let _ = super::flush().unwrap();
+
+    let n = Spi::get_one::<i64>("SELECT count(*) FROM mr_audit_log WHERE xid = pg_current_xact_id()").unwrap().unwrap();
+    assert_eq!(n, 3);
+
+    let (command, context, src, outcome) = Spi::get_four::<String, String, String, String>(
+      "SELECT command, context, arguments->>'src', outcome FROM mr_audit_log WHERE xid = pg_current_xact_id() ORDER BY id LIMIT 1"
+    ).unwrap();
+
+    assert_eq!(command, Some(meritrank_service::protocol::CMD_PUT_EDGE.to_string()));
+    assert_eq!(context, Some(x));
+    assert_eq!(src,     Some(u1));
+    assert_eq!(outcome, Some("ok".to_string()));
+  }
+
+  #[pg_test]
+  fn audit_failure() {
+    use crate::mock::{Faults, MockService};
+
+    let url     = format!("ipc:///tmp/pgmer2-mock-audit-{}", std::process::id());
+    let service = MockService::start(&url).unwrap();
+    crate::testing::shared();
+    crate::set_service_url(Some(url));
+    let _ = crate::mr_nodelist(None, None).unwrap();
+
+    Spi::run("SET meritrank.audit = on").unwrap();
+    service.set_faults(Faults { garbage : true, ..Faults::default() });
+
+    assert!(crate::mr_put_edge(Some("U1"), Some("U2"), Some(1.0), None, None, None).is_err());
+
+    service.set_faults(Faults::default());
+    Spi::run("RESET meritrank.audit").unwrap();
+    crate::set_service_url(None);
+
+    let _ = super::flush().unwrap();
+
+    let outcome = Spi::get_one::<String>("SELECT outcome FROM mr_audit_log WHERE xid = pg_current_xact_id()").unwrap().unwrap();
+    assert!(outcome.starts_with("error: "));
+  }
+}
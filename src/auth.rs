@@ -0,0 +1,124 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::error::Error;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::guc;
+
+//  ================================================================
+//
+//    Request signing
+//
+//  ================================================================
+
+//  When `meritrank.service_secret` is set, every encoded `Command`
+//  is wrapped into an envelope
+//
+//    (scheme, timestamp_msec, signature, payload)
+//
+//  where the signature is HMAC-SHA256 of the big-endian timestamp
+//  followed by the payload. The service verifies the signature and
+//  rejects stale timestamps.
+
+pub const SCHEME : &str = "hmac-sha256";
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn signature(
+  secret         : &[u8],
+  timestamp_msec : u64,
+  payload        : &[u8],
+) -> Result<Vec<u8>, Box<dyn Error + 'static>> {
+  let mut mac = HmacSha256::new_from_slice(secret)?;
+  mac.update(&timestamp_msec.to_be_bytes());
+  mac.update(payload);
+  return Ok(mac.finalize().into_bytes().to_vec());
+}
+
+pub fn sign(payload : &[u8]) -> Result<Vec<u8>, Box<dyn Error + 'static>> {
+  let secret = match guc::service_secret() {
+    Some(x) => x,
+    None    => return Ok(payload.to_vec()),
+  };
+
+  let timestamp_msec = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+
+  return Ok(rmp_serde::to_vec(&(
+    SCHEME,
+    timestamp_msec,
+    signature(&secret, timestamp_msec, payload)?,
+    payload
+  ))?);
+}
+
+//  Service-side check, used by the mock services in tests.
+//  Returns the signed payload if the envelope is valid.
+#[cfg(any(test, feature = "pg_test"))]
+pub fn verify(secret : &[u8], envelope : &[u8]) -> Option<Vec<u8>> {
+  let (scheme, timestamp_msec, sig, payload) : (String, u64, Vec<u8>, Vec<u8>) =
+    rmp_serde::from_slice(envelope).ok()?;
+
+  if scheme != SCHEME {
+    return None;
+  }
+
+  let mut mac = HmacSha256::new_from_slice(secret).ok()?;
+  mac.update(&timestamp_msec.to_be_bytes());
+  mac.update(&payload);
+  mac.verify_slice(&sig).ok()?;
+
+  return Some(payload);
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use crate::mock::{MockService, MOCK_VERSION};
+
+  #[pg_test]
+  fn rejects_unsigned() {
+    let url     = format!("ipc:///tmp/pgmer2-auth-{}", std::process::id());
+    let service = MockService::start(&url).unwrap();
+    service.require_secret(b"test-secret");
+    crate::set_service_url(Some(url));
+
+    assert_ne!(crate::mr_service(), MOCK_VERSION);
+
+    Spi::run("SET meritrank.service_secret = 'wrong-secret'").unwrap();
+    assert_ne!(crate::mr_service(), MOCK_VERSION);
+
+    Spi::run("SET meritrank.service_secret = 'test-secret'").unwrap();
+    assert_eq!(crate::mr_service(), MOCK_VERSION);
+
+    Spi::run("RESET meritrank.service_secret").unwrap();
+    crate::set_service_url(None);
+  }
+  #[pg_test]
+  fn secret_hidden() {
+    let hidden = Spi::get_one::<bool>(
+      "SELECT NOT EXISTS (SELECT FROM pg_settings WHERE name = 'meritrank.service_secret')"
+    ).unwrap().unwrap();
+
+    //  Superusers still see it.
+    assert!(!hidden);
+
+    Spi::run("CREATE ROLE mr_auth_test_user").unwrap();
+    Spi::run("SET ROLE mr_auth_test_user").unwrap();
+
+    let visible = Spi::get_one::<bool>(
+      "SELECT EXISTS (SELECT FROM pg_settings WHERE name = 'meritrank.service_secret')"
+    ).unwrap().unwrap();
+
+    Spi::run("RESET ROLE").unwrap();
+    Spi::run("DROP ROLE mr_auth_test_user").unwrap();
+
+    assert!(!visible);
+  }
+}
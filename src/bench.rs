@@ -0,0 +1,216 @@
+use pgrx::*;
+use pgrx::prelude::*;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::time::Instant;
+
+use crate::generate::{generate, GraphParams};
+use crate::stats;
+
+//  ================================================================
+//
+//    Benchmarks
+//
+//  ================================================================
+//
+//  Times connector calls against a generated graph. Overhead is the
+//  time spent in the connector itself, i.e. everything but the
+//  round trip to the service: encoding, decoding and building tuples.
+//
+//  The graph is generated in a context of its own, which must be
+//  empty, and removed afterwards.
+
+const BATCH : usize = 100;
+
+type Row = (String, i32, f64, f64, Option<f64>, Option<f64>, Option<f64>, f64);
+
+fn measure(
+  operation  : &str,
+  iterations : i32,
+  mut f      : impl FnMut(usize) -> Result<(), Box<dyn Error + 'static>>,
+) -> Result<Row, Box<dyn Error + 'static>> {
+  let mut latencies     : Vec<f64> = vec![];
+  let mut overhead_usec : f64      = 0.0;
+
+  let begin = Instant::now();
+
+  for n in 0..iterations as usize {
+    let request_usec = stats::backend_request_usec();
+    let start        = Instant::now();
+
+    f(n)?;
+
+    let elapsed = start.elapsed();
+    latencies.push(elapsed.as_secs_f64() * 1000.0);
+    overhead_usec += elapsed.as_micros() as f64 - (stats::backend_request_usec() - request_usec) as f64;
+  }
+
+  let total_msec = begin.elapsed().as_secs_f64() * 1000.0;
+
+  latencies.sort_by(|a, b| a.total_cmp(b));
+
+  return Ok((
+    operation.to_string(),
+    iterations,
+    total_msec,
+    iterations as f64 / (total_msec / 1000.0),
+    crate::percentile(&latencies, 50.0),
+    crate::percentile(&latencies, 90.0),
+    crate::percentile(&latencies, 99.0),
+    overhead_usec / 1000.0 / iterations as f64,
+  ));
+}
+
+#[pg_extern]
+fn mr_benchmark(
+  iterations : default!(Option<i32>, "100"),
+  context    : default!(Option<&str>, "'mr_benchmark'"),
+) -> Result<
+  TableIterator<'static, (
+    name!(operation,     String),
+    name!(calls,         i32),
+    name!(total_msec,    f64),
+    name!(calls_per_sec, f64),
+    name!(p50_msec,      Option<f64>),
+    name!(p90_msec,      Option<f64>),
+    name!(p99_msec,      Option<f64>),
+    name!(overhead_msec, f64),
+  )>,
+  Box<dyn Error + 'static>,
+> {
+  let iterations = iterations.unwrap_or(100);
+  let context    = context.unwrap_or("mr_benchmark");
+
+  if iterations < 1 {
+    return Err(Box::from("iterations should be positive"));
+  }
+  if context.is_empty() {
+    return Err(Box::from("benchmark needs a context of its own"));
+  }
+  if crate::mr_nodelist(Some(context), None)?.next().is_some() {
+    return Err(Box::from(format!("context \"{}\" should be empty", context)));
+  }
+
+  let params = GraphParams {
+    users          : 200,
+    beacons        : 50,
+    comments       : 200,
+    avg_degree     : 8.0,
+    negative_ratio : 0.1,
+    seed           : 1,
+  };
+
+  let edges = generate(&params)?;
+  let rows  = crate::put_edges(&edges, context).and_then(|_| run(iterations, context, &params, &edges));
+
+  //  Remove the graph even if the benchmark failed.
+  let nodes : BTreeSet<&str> = edges.iter().flat_map(|(src, dst, _)| [src.as_str(), dst.as_str()]).collect();
+  for node in nodes {
+    let _ = crate::mr_delete_node(Some(node), Some(context), Some(false))?;
+  }
+  let _ = crate::mr_sync(Some(60000))?;
+
+  return Ok(TableIterator::new(rows?.into_iter()));
+}
+
+fn run(
+  iterations : i32,
+  context    : &str,
+  params     : &GraphParams,
+  edges      : &[(String, String, f64)],
+) -> Result<Vec<Row>, Box<dyn Error + 'static>> {
+  let _ = crate::mr_sync(Some(60000))?;
+
+  let user = |n : usize| format!("U{:012x}", n as u64 % params.users);
+  let ego  = user(0);
+
+  let rows = vec![
+    measure("mr_node_score", iterations, |n| {
+      let _ = crate::mr_node_score(Some(ego.as_str()), Some(user(n + 1).as_str()), Some(context), None)?.count();
+      Ok(())
+    })?,
+
+    measure("mr_scores", iterations, |_| {
+      let _ = crate::mr_scores(
+        Some(ego.as_str()), Some(false), Some(context), Some(""),
+        None, None, None, None,
+        Some(0), Some(BATCH as i32), None
+      )?.count();
+      Ok(())
+    })?,
+
+    measure("mr_graph", iterations, |n| {
+      let _ = crate::mr_graph(
+        Some(ego.as_str()), Some(user(n + 1).as_str()), Some(context), Some(false),
+        Some(0), Some(BATCH as i32), None
+      )?.count();
+      Ok(())
+    })?,
+
+    //  Puts back existing edges, so the graph doesn't change.
+    measure("mr_put_edge", iterations, |n| {
+      let (src, dst, weight) = &edges[n % edges.len()];
+      let _ = crate::mr_put_edge(Some(src.as_str()), Some(dst.as_str()), Some(*weight), Some(context), None, None)?.count();
+      Ok(())
+    })?,
+
+    measure(&format!("put_edges x{}", BATCH), iterations, |n| {
+      let start = (n * BATCH) % edges.len();
+      let end   = (start + BATCH).min(edges.len());
+      crate::put_edges(&edges[start..end], context)
+    })?,
+
+    measure(&format!("make_setof_edge x{}", BATCH), iterations, |_| {
+      let _ = crate::make_setof_edge(&edges[..BATCH.min(edges.len())].to_vec())?.count();
+      Ok(())
+    })?,
+  ];
+
+  return Ok(rows);
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use crate::testing::Scope;
+
+  #[pg_test]
+  fn benchmark() {
+    let t = Scope::new();
+    let x = t.context("X");
+
+    let rows : Vec<_> = crate::bench::mr_benchmark(Some(10), Some(x.as_str()))
+      .unwrap()
+      .collect();
+
+    assert_eq!(rows.len(), 6);
+
+    for (_, calls, total, calls_per_sec, p50, p90, p99, overhead) in rows {
+      assert_eq!(calls, 10);
+      assert!(total > 0.0 && calls_per_sec > 0.0);
+      assert!(p50.is_some() && p50 <= p90 && p90 <= p99);
+      assert!(overhead >= 0.0);
+    }
+
+    //  The graph is removed.
+    assert_eq!(crate::mr_nodelist(Some(x.as_str()), None).unwrap().count(), 0);
+
+    assert!(crate::bench::mr_benchmark(Some(10), Some("")).is_err());
+  }
+
+  #[pg_test]
+  fn benchmark_nonempty() {
+    let t = Scope::new();
+    let x = t.context("X");
+
+    let _ = crate::mr_put_edge(Some(t.node("U1").as_str()), Some(t.node("U2").as_str()), Some(1.0), Some(x.as_str()), Some(true), None).unwrap();
+    assert!(crate::bench::mr_benchmark(Some(10), Some(x.as_str())).is_err());
+  }
+}
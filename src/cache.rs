@@ -0,0 +1,366 @@
+use lazy_static::lazy_static;
+use pgrx::*;
+use pgrx::prelude::*;
+use meritrank_service::protocol::*;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::guc;
+use crate::seq;
+use crate::shared_cache;
+
+//  ================================================================
+//
+//    Per-backend response cache
+//
+//  ================================================================
+//
+//  Responses to read commands are kept by the encoded request, which
+//  includes command, context and arguments. Least recently used
+//  entries are evicted when the cache is full. Mutations issued by
+//  this backend drop the whole cache once the service acknowledges
+//  them, writes of other backends are only picked up after the TTL.
+//
+//  A non-blocking write is acknowledged before it is applied, so
+//  responses are not stored while writes of this backend may still
+//  be queued, i.e. until a sync confirms them, see `seq.rs`.
+//
+//  Scores also go through the shared cache, see `shared_cache.rs`.
+
+pub const CACHEABLE : &[&str] = &[
+  CMD_NODE_SCORE,
+  CMD_SCORES,
+  CMD_GRAPH,
+  CMD_NODE_LIST,
+  CMD_EDGES,
+  CMD_CONNECTED,
+  CMD_MUTUAL_SCORES,
+];
+
+const INVALIDATING : &[&str] = &[
+  CMD_RESET,
+  CMD_RECALCULATE_ZERO,
+  CMD_CREATE_CONTEXT,
+  CMD_PUT_EDGE,
+  CMD_DELETE_EDGE,
+  CMD_DELETE_NODE,
+];
+
+struct Entry {
+  response  : Vec<u8>,
+  stored_at : Instant,
+  used      : u64,
+}
+
+#[derive(Default)]
+struct Cache {
+  entries       : HashMap<Vec<u8>, Entry>,
+  //  Last use tick to key, oldest first.
+  lru           : BTreeMap<u64, Vec<u8>>,
+  tick          : u64,
+  bytes         : usize,
+  hits          : u64,
+  misses        : u64,
+  evictions     : u64,
+  invalidations : u64,
+}
+
+impl Cache {
+  fn remove(&mut self, key : &[u8]) {
+    if let Some(entry) = self.entries.remove(key) {
+      self.lru.remove(&entry.used);
+      self.bytes -= key.len() + entry.response.len();
+    }
+  }
+
+  fn clear(&mut self) {
+    self.entries.clear();
+    self.lru.clear();
+    self.bytes = 0;
+  }
+}
+
+lazy_static! {
+  static ref CACHE : Mutex<Cache> = Mutex::new(Cache::default());
+}
+
+fn capacity() -> usize {
+  guc::CACHE_SIZE.get().max(0) as usize
+}
+
+fn ttl() -> Option<Duration> {
+  match guc::CACHE_TTL.get() {
+    0 => None,
+    n => Some(Duration::from_millis(n as u64)),
+  }
+}
+
+pub struct Key {
+  payload    : Vec<u8>,
+  //  Shared cache generation at the time of the request, so a response
+  //  that raced with a mutation is stored as already stale.
+  generation : u64,
+}
+
+//  Returns the cache key for a request if its response may be cached.
+pub fn key(payload : &[u8]) -> Option<Key> {
+  let (id, _) = crate::command_header(payload);
+
+  if !CACHEABLE.contains(&id.as_str()) || (capacity() == 0 && !shared_cache::enabled()) {
+    return None;
+  }
+
+  return Some(Key {
+    payload    : payload.to_vec(),
+    generation : shared_cache::generation(),
+  });
+}
+
+pub fn get(key : &Key) -> Option<Vec<u8>> {
+  if capacity() > 0 {
+    if let Some(response) = get_local(&key.payload) {
+      return Some(response);
+    }
+  }
+  return shared_cache::get(&key.payload);
+}
+
+pub fn put(key : Key, response : &[u8]) {
+  //  The response may not include writes of this backend yet.
+  let (_, context) = crate::command_header(&key.payload);
+  if seq::pending(&context) {
+    return;
+  }
+  shared_cache::put(&key.payload, response, key.generation);
+  put_local(key.payload, response);
+}
+
+//  Called for every acknowledged request. Requests that change the
+//  graph invalidate both caches.
+pub fn acknowledged(payload : &[u8]) {
+  let (id, _) = crate::command_header(payload);
+
+  if INVALIDATING.contains(&id.as_str()) {
+    invalidate();
+    shared_cache::invalidate();
+  }
+}
+
+fn get_local(key : &[u8]) -> Option<Vec<u8>> {
+  let mut cache = CACHE.lock().unwrap();
+
+  let expired = match cache.entries.get(key) {
+    Some(entry) => ttl().map_or(false, |ttl| entry.stored_at.elapsed() > ttl),
+    None        => {
+      cache.misses += 1;
+      return None;
+    },
+  };
+
+  if expired {
+    cache.remove(key);
+    cache.misses += 1;
+    return None;
+  }
+
+  cache.tick += 1;
+  let tick     = cache.tick;
+  let entry    = cache.entries.get_mut(key).unwrap();
+  let previous = std::mem::replace(&mut entry.used, tick);
+  let response = entry.response.clone();
+
+  let key = cache.lru.remove(&previous).unwrap();
+  cache.lru.insert(tick, key);
+  cache.hits += 1;
+
+  return Some(response);
+}
+
+fn put_local(key : Vec<u8>, response : &[u8]) {
+  let capacity  = capacity();
+  let mut cache = CACHE.lock().unwrap();
+
+  if capacity == 0 {
+    return;
+  }
+
+  cache.remove(&key);
+
+  while cache.entries.len() >= capacity {
+    let oldest = match cache.lru.values().next() {
+      Some(key) => key.clone(),
+      None      => break,
+    };
+    cache.remove(&oldest);
+    cache.evictions += 1;
+  }
+
+  cache.tick  += 1;
+  let tick     = cache.tick;
+  cache.bytes += key.len() + response.len();
+  cache.lru.insert(tick, key.clone());
+  cache.entries.insert(key, Entry {
+    response  : response.to_vec(),
+    stored_at : Instant::now(),
+    used      : tick,
+  });
+}
+
+pub fn invalidate() {
+  let mut cache = CACHE.lock().unwrap();
+  if !cache.entries.is_empty() {
+    cache.clear();
+    cache.invalidations += 1;
+  }
+}
+
+//  ================================================================
+//
+//    SQL
+//
+//  ================================================================
+
+#[pg_extern]
+fn mr_cache_stats() -> TableIterator<'static, (
+  name!(entries,       i64),
+  name!(bytes,         i64),
+  name!(hits,          i64),
+  name!(misses,        i64),
+  name!(evictions,     i64),
+  name!(invalidations, i64),
+)> {
+  let cache = CACHE.lock().unwrap();
+
+  TableIterator::once((
+    cache.entries.len()   as i64,
+    cache.bytes           as i64,
+    cache.hits            as i64,
+    cache.misses          as i64,
+    cache.evictions       as i64,
+    cache.invalidations   as i64,
+  ))
+}
+
+#[pg_extern]
+fn mr_cache_clear() -> Result<&'static str, Box<dyn Error + 'static>> {
+  *CACHE.lock().unwrap() = Cache::default();
+  return Ok("Ok");
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use crate::testing::Scope;
+
+  fn stats() -> (i64, i64, i64, i64) {
+    let (entries, _, hits, misses, _, invalidations) =
+      crate::cache::mr_cache_stats().next().unwrap();
+    (entries, hits, misses, invalidations)
+  }
+
+  fn scores(t : &Scope, x : &str) -> usize {
+    crate::mr_scores(
+      Some(t.node("U1").as_str()), Some(false), Some(x), Some("U"),
+      None, None, None, None, None, None, None
+    ).unwrap().count()
+  }
+
+  #[pg_test]
+  fn cache_disabled() {
+    let t = Scope::new();
+    let x = t.context("X");
+
+    let _ = crate::cache::mr_cache_clear().unwrap();
+    let _ = crate::mr_put_edge(Some(t.node("U1").as_str()), Some(t.node("U2").as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+    let _ = scores(&t, &x);
+
+    assert_eq!(stats(), (0, 0, 0, 0));
+  }
+
+  #[pg_test]
+  fn cache_hits_and_invalidation() {
+    let t = Scope::new();
+    let x = t.context("X");
+    let (u1, u2, u3) = (t.node("U1"), t.node("U2"), t.node("U3"));
+
+    let _ = crate::cache::mr_cache_clear().unwrap();
+    Spi::run("SET meritrank.cache_size = 16").unwrap();
+
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u2.as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+
+    assert_eq!(scores(&t, &x), 2);
+    assert_eq!(scores(&t, &x), 2);
+    assert_eq!(stats(), (1, 1, 1, 0));
+
+    //  Writes of this backend are visible right away.
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u3.as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+
+    assert_eq!(scores(&t, &x), 3);
+    assert_eq!(stats(), (1, 1, 2, 1));
+
+    let _ = crate::cache::mr_cache_clear().unwrap();
+    assert_eq!(stats(), (0, 0, 0, 0));
+
+    Spi::run("RESET meritrank.cache_size").unwrap();
+  }
+
+  #[pg_test]
+  fn cache_own_writes() {
+    let t = Scope::new();
+    let x = t.context("X");
+    let (u1, u2) = (t.node("U1"), t.node("U2"));
+
+    let _ = crate::cache::mr_cache_clear().unwrap();
+    Spi::run("SET meritrank.cache_size = 16").unwrap();
+    Spi::run("SET meritrank.cache_ttl = 0").unwrap();
+
+    assert_eq!(scores(&t, &x), 0);
+
+    //  Reads right after a non-blocking write may not see it yet, but
+    //  such responses are not kept, so the write shows up without a sync.
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u2.as_str()), Some(1.0), Some(x.as_str()), Some(false), None).unwrap();
+
+    let end = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while scores(&t, &x) != 2 {
+      assert!(std::time::Instant::now() < end, "the write is not visible");
+      std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    Spi::run("RESET meritrank.cache_ttl").unwrap();
+    Spi::run("RESET meritrank.cache_size").unwrap();
+    let _ = crate::cache::mr_cache_clear().unwrap();
+  }
+
+  #[pg_test]
+  fn cache_ttl_and_eviction() {
+    let t = Scope::new();
+
+    let _ = crate::cache::mr_cache_clear().unwrap();
+    Spi::run("SET meritrank.cache_size = 1").unwrap();
+    Spi::run("SET meritrank.cache_ttl = 50").unwrap();
+
+    let _ = scores(&t, &t.context("X"));
+    let _ = scores(&t, &t.context("Y"));
+    assert_eq!(stats(), (1, 0, 2, 0));
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let _ = scores(&t, &t.context("Y"));
+    assert_eq!(stats(), (1, 0, 3, 0));
+
+    Spi::run("RESET meritrank.cache_ttl").unwrap();
+    Spi::run("RESET meritrank.cache_size").unwrap();
+    let _ = crate::cache::mr_cache_clear().unwrap();
+  }
+}
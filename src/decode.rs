@@ -0,0 +1,44 @@
+use meritrank_service::protocol::decode_response;
+use serde::de::Deserialize;
+use std::error::Error;
+
+//  ================================================================
+//
+//    Response decoding
+//
+//  ================================================================
+//
+//  Everything the connector reads from the service goes through
+//  here. This module doesn't depend on pgrx, so the fuzz targets in
+//  `fuzz/` can include it as is.
+
+pub type Edges        = Vec<(String, String, f64)>;
+pub type SrcEdges     = Vec<(String, f64)>;
+pub type Nodes        = Vec<(String,)>;
+pub type Links        = Vec<(String, String)>;
+pub type MutualScores = Vec<(String, f64, f64)>;
+pub type Filter       = Vec<u8>;
+
+pub fn response<T>(bytes : &[u8]) -> Result<T, Box<dyn Error + 'static>>
+  where T : for<'a> Deserialize<'a>
+{
+  decode_response(bytes).map_err(|e| e.into())
+}
+
+//  CMD_VERSION responds with a bare string.
+pub fn version(bytes : &[u8]) -> Result<String, Box<dyn Error + 'static>> {
+  rmp_serde::from_slice(bytes).map_err(|e| e.into())
+}
+
+//  Decodes `bytes` as every response type, for fuzzing.
+#[allow(dead_code)]
+pub fn all(bytes : &[u8]) {
+  let _ = response::<()>(bytes);
+  let _ = response::<Edges>(bytes);
+  let _ = response::<SrcEdges>(bytes);
+  let _ = response::<Nodes>(bytes);
+  let _ = response::<Links>(bytes);
+  let _ = response::<MutualScores>(bytes);
+  let _ = response::<Filter>(bytes);
+  let _ = version(bytes);
+}
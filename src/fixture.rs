@@ -0,0 +1,146 @@
+use pgrx::*;
+use std::error::Error;
+
+//  ================================================================
+//
+//    Fixtures
+//
+//  ================================================================
+//
+//  A fixture is a CSV file with a `src,dst,weight` header and one
+//  edge per line. Empty lines and lines starting with `#` are
+//  ignored. Bundled fixtures are referred to by name, anything with
+//  a slash is a path on the database server.
+
+const BUNDLED : &[(&str, &str)] = &[
+  ("testing", include_str!("../fixtures/testing.csv")),
+  ("star",    include_str!("../fixtures/star.csv")),
+  ("chain",   include_str!("../fixtures/chain.csv")),
+  ("sybil",   include_str!("../fixtures/sybil.csv")),
+];
+
+const HEADER : &str = "src,dst,weight";
+
+pub fn parse(text : &str) -> Result<Vec<(String, String, f64)>, Box<dyn Error + 'static>> {
+  let mut lines = text
+    .lines()
+    .enumerate()
+    .map(|(n, line)| (n + 1, line.trim()))
+    .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+  match lines.next() {
+    Some((_, HEADER)) => {},
+    _                 => return Err(Box::from(format!("fixture should start with \"{}\"", HEADER))),
+  }
+
+  let mut edges = vec![];
+
+  for (n, line) in lines {
+    let fields : Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+
+    let (src, dst, weight) = match fields[..] {
+      [src, dst, weight] if !src.is_empty() && !dst.is_empty() => (src, dst, weight),
+      _ => return Err(Box::from(format!("fixture line {}: expected 3 fields", n))),
+    };
+
+    let weight = weight
+      .parse::<f64>()
+      .map_err(|e| format!("fixture line {}: {}", n, e))?;
+
+    edges.push((src.to_string(), dst.to_string(), weight));
+  }
+
+  return Ok(edges);
+}
+
+fn can_read_server_files() -> Result<bool, Box<dyn Error + 'static>> {
+  Ok(Spi::get_one::<bool>(
+    "SELECT pg_has_role(current_user, 'pg_read_server_files', 'MEMBER')"
+  )?.unwrap_or(false))
+}
+
+fn read(path : &str) -> Result<String, Box<dyn Error + 'static>> {
+  if !path.contains('/') {
+    return match BUNDLED.iter().find(|(name, _)| *name == path) {
+      Some((_, text)) => Ok(text.to_string()),
+      None            => Err(Box::from(format!("no bundled fixture \"{}\"", path))),
+    };
+  }
+
+  if !can_read_server_files()? {
+    return Err(Box::from("loading a fixture from a file requires pg_read_server_files"));
+  }
+
+  return std::fs::read_to_string(path)
+    .map_err(|e| Box::from(format!("could not read fixture \"{}\": {}", path, e)));
+}
+
+pub fn load(path : &str, context : &str) -> Result<i64, Box<dyn Error + 'static>> {
+  let edges = parse(&read(path)?)?;
+  crate::put_edges(&edges, context)?;
+  return Ok(edges.len() as i64);
+}
+
+#[pg_extern]
+fn mr_load_fixture(
+  path    : Option<&str>,
+  context : default!(Option<&str>, "''")
+) -> Result<i64, Box<dyn Error + 'static>> {
+  let path    = path.expect("path should not be null");
+  let context = context.unwrap_or("");
+
+  return load(path, context);
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use crate::testing::Scope;
+
+  #[pg_test]
+  fn fixture_parse() {
+    let edges = super::parse("# comment\nsrc,dst,weight\n\nU1,U2,1.5\nU2,B1,-1\n").unwrap();
+
+    assert_eq!(edges, vec![
+      ("U1".to_string(), "U2".to_string(),  1.5),
+      ("U2".to_string(), "B1".to_string(), -1.0),
+    ]);
+
+    assert!(super::parse("U1,U2,1.0\n").is_err());
+    assert!(super::parse("src,dst,weight\nU1,U2\n").is_err());
+    assert!(super::parse("src,dst,weight\nU1,U2,x\n").is_err());
+  }
+
+  #[pg_test]
+  fn fixture_bundled() {
+    for (name, text) in super::BUNDLED {
+      assert!(!super::parse(text).unwrap().is_empty(), "{}", name);
+    }
+
+    assert!(super::read("nonexistent").is_err());
+  }
+
+  #[pg_test]
+  fn fixture_load() {
+    let t = Scope::new();
+    let x = t.context("X");
+
+    let n = Spi::get_one_with_args::<i64>(
+      "SELECT mr_load_fixture('star', $1)",
+      vec![(PgBuiltInOids::TEXTOID.oid(), x.clone().into_datum())],
+    ).unwrap().unwrap();
+
+    assert_eq!(n, 16);
+
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+
+    assert_eq!(crate::mr_edgelist(Some(x.as_str()), None).unwrap().count(), 16);
+  }
+}
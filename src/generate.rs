@@ -0,0 +1,244 @@
+use pgrx::*;
+use std::collections::HashSet;
+use std::error::Error;
+
+//  ================================================================
+//
+//    Synthetic graphs
+//
+//  ================================================================
+//
+//  Users put edges to random users, beacons and comments. Every beacon
+//  and comment has an edge to its author. Node ids follow the same
+//  U/B/C conventions as the testing fixture.
+
+//  SplitMix64, so the graph for a given seed doesn't depend on the
+//  version of any external crate.
+pub struct SplitMix64 {
+  state : u64,
+}
+
+impl SplitMix64 {
+  pub fn new(seed : u64) -> SplitMix64 {
+    SplitMix64 { state : seed }
+  }
+
+  pub fn next(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z  = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    return z ^ (z >> 31);
+  }
+
+  //  Uniform in [0, n).
+  pub fn below(&mut self, n : u64) -> u64 {
+    return ((self.next() as u128 * n as u128) >> 64) as u64;
+  }
+
+  //  Uniform in [0, 1).
+  pub fn unit(&mut self) -> f64 {
+    return (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+  }
+}
+
+pub struct GraphParams {
+  pub users          : u64,
+  pub beacons        : u64,
+  pub comments       : u64,
+  pub avg_degree     : f64,
+  pub negative_ratio : f64,
+  pub seed           : u64,
+}
+
+//  All edges are built in memory before they are put, so the size of
+//  a graph is limited.
+const MAX_EDGES : u64 = 5_000_000;
+
+fn node_id(kind : char, n : u64) -> String {
+  format!("{}{:012x}", kind, n)
+}
+
+fn check(params : &GraphParams) -> Result<(), Box<dyn Error + 'static>> {
+  if params.users == 0 && (params.beacons > 0 || params.comments > 0) {
+    return Err(Box::from("beacons and comments need at least one user"));
+  }
+  if params.avg_degree.is_nan() || params.avg_degree < 0.0 {
+    return Err(Box::from("avg_degree should not be negative"));
+  }
+  if !(0.0..=1.0).contains(&params.negative_ratio) {
+    return Err(Box::from("negative_ratio should be between 0 and 1"));
+  }
+  return Ok(());
+}
+
+pub fn generate(params : &GraphParams) -> Result<Vec<(String, String, f64)>, Box<dyn Error + 'static>> {
+  check(params)?;
+
+  //  Each user may have at most one edge to every other node. Edges
+  //  are sampled with rejection, so no more than half of the possible
+  //  ones are taken, which keeps the expected retries per edge below 2.
+  let authored = params.beacons
+    .checked_add(params.comments)
+    .ok_or("too many nodes")?;
+  let targets  = params.users
+    .checked_add(authored)
+    .ok_or("too many nodes")?;
+  let pairs    = params.users
+    .checked_mul(targets.saturating_sub(1))
+    .ok_or("too many nodes")?;
+  let total    = ((params.users as f64 * params.avg_degree).round() as u64)
+    .min(pairs / 2);
+
+  //  Checked before anything is allocated.
+  if authored.saturating_add(total) > MAX_EDGES {
+    return Err(Box::from(format!("at most {} edges can be generated", MAX_EDGES)));
+  }
+
+  let mut rng   = SplitMix64::new(params.seed);
+  let mut edges = Vec::with_capacity((authored + total) as usize);
+
+  //  Authors of beacons and comments.
+  for (kind, count) in [('B', params.beacons), ('C', params.comments)] {
+    for n in 0..count {
+      let author = rng.below(params.users);
+      edges.push((node_id(kind, n), node_id('U', author), 1.0));
+    }
+  }
+
+  let mut seen = HashSet::new();
+
+  while (seen.len() as u64) < total {
+    let src = rng.below(params.users);
+    let dst = rng.below(targets);
+
+    if dst == src || !seen.insert((src, dst)) {
+      continue;
+    }
+
+    let dst = if dst < params.users {
+      node_id('U', dst)
+    } else if dst < params.users + params.beacons {
+      node_id('B', dst - params.users)
+    } else {
+      node_id('C', dst - params.users - params.beacons)
+    };
+
+    let weight = if rng.unit() < params.negative_ratio {
+      -((1 + rng.below(3)) as f64)
+    } else {
+      (1 + rng.below(9)) as f64
+    };
+
+    edges.push((node_id('U', src), dst, weight));
+  }
+
+  return Ok(edges);
+}
+
+#[pg_extern]
+fn mr_generate_graph(
+  users          : Option<i32>,
+  beacons        : default!(Option<i32>, "0"),
+  comments       : default!(Option<i32>, "0"),
+  avg_degree     : default!(Option<f64>, "10"),
+  negative_ratio : default!(Option<f64>, "0.1"),
+  seed           : default!(Option<i32>, "0"),
+  context        : default!(Option<&str>, "''")
+) -> Result<i64, Box<dyn Error + 'static>> {
+  let users = users.expect("users should not be null");
+  let count = |x : Option<i32>, name : &str| -> Result<u64, Box<dyn Error + 'static>> {
+    match x.unwrap_or(0) {
+      n if n >= 0 => Ok(n as u64),
+      _           => Err(Box::from(format!("{} should not be negative", name))),
+    }
+  };
+
+  let params = GraphParams {
+    users          : count(Some(users), "users")?,
+    beacons        : count(beacons,  "beacons")?,
+    comments       : count(comments, "comments")?,
+    avg_degree     : avg_degree.unwrap_or(10.0),
+    negative_ratio : negative_ratio.unwrap_or(0.1),
+    seed           : seed.unwrap_or(0) as u64,
+  };
+  let context = context.unwrap_or("");
+
+  let edges = generate(&params)?;
+  crate::put_edges(&edges, context)?;
+  return Ok(edges.len() as i64);
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use crate::testing::Scope;
+  use super::*;
+
+  fn params(seed : u64) -> GraphParams {
+    GraphParams {
+      users          : 50,
+      beacons        : 20,
+      comments       : 30,
+      avg_degree     : 4.0,
+      negative_ratio : 0.2,
+      seed,
+    }
+  }
+
+  #[pg_test]
+  fn generate_deterministic() {
+    let a = generate(&params(1)).unwrap();
+    let b = generate(&params(1)).unwrap();
+    let c = generate(&params(2)).unwrap();
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+
+    //  Authors plus user edges.
+    assert_eq!(a.len(), 20 + 30 + 200);
+    assert!(a.iter().any(|x| x.2 < 0.0));
+    assert!(a.iter().all(|x| x.0 != x.1));
+  }
+
+  #[pg_test]
+  fn generate_invalid() {
+    assert!(generate(&GraphParams { negative_ratio : 2.0, ..params(0) }).is_err());
+    assert!(generate(&GraphParams { avg_degree : -1.0, ..params(0) }).is_err());
+    assert!(generate(&GraphParams { users : 0, ..params(0) }).is_err());
+    assert!(generate(&GraphParams { users : u64::MAX / 2, beacons : 0, comments : 0, ..params(0) }).is_err());
+    assert!(generate(&GraphParams { users : i32::MAX as u64, ..params(0) }).is_err());
+    assert!(generate(&GraphParams { beacons : i32::MAX as u64, comments : i32::MAX as u64, ..params(0) }).is_err());
+    assert!(generate(&GraphParams { beacons : u64::MAX, comments : 1, ..params(0) }).is_err());
+  }
+
+  #[pg_test]
+  fn generate_dense() {
+    //  Asks for every possible edge, half of them are generated.
+    let edges = generate(&GraphParams { users : 40, beacons : 0, comments : 0, avg_degree : 100.0, ..params(0) }).unwrap();
+    assert_eq!(edges.len(), 40 * 39 / 2);
+  }
+
+  #[pg_test]
+  fn generate_graph() {
+    let t = Scope::new();
+    let x = t.context("X");
+
+    let n = crate::generate::mr_generate_graph(
+      Some(10), Some(5), Some(5), Some(2.0), Some(0.0), Some(7), Some(x.as_str())
+    ).unwrap();
+
+    assert_eq!(n, 5 + 5 + 20);
+
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+
+    assert_eq!(crate::mr_edgelist(Some(x.as_str()), None).unwrap().count(), 30);
+  }
+}
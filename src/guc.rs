@@ -0,0 +1,249 @@
+use pgrx::*;
+use std::ffi::CStr;
+
+//  ================================================================
+//
+//    Settings
+//
+//  ================================================================
+
+//  What to do when the service version is outside of the supported range.
+#[derive(PostgresGucEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VersionCheck {
+  Error,
+  Warning,
+  Off,
+}
+
+pub static VERSION_CHECK : GucSetting<VersionCheck> = GucSetting::<VersionCheck>::new(VersionCheck::Error);
+
+pub static LOG_MIN_DURATION : GucSetting<i32> = GucSetting::<i32>::new(-1);
+
+pub static LOG_REQUESTS : GucSetting<bool> = GucSetting::<bool>::new(false);
+
+pub static AUDIT : GucSetting<bool> = GucSetting::<bool>::new(false);
+
+pub static CACHE_SIZE : GucSetting<i32> = GucSetting::<i32>::new(0);
+
+pub static CACHE_TTL : GucSetting<i32> = GucSetting::<i32>::new(1000);
+
+pub static SYNCHRONOUS_WRITES : GucSetting<bool> = GucSetting::<bool>::new(false);
+
+pub static READ_YOUR_WRITES : GucSetting<bool> = GucSetting::<bool>::new(false);
+
+pub static SHARED_CACHE_SIZE : GucSetting<i32> = GucSetting::<i32>::new(0);
+
+pub static SHARED_CACHE_TTL : GucSetting<i32> = GucSetting::<i32>::new(1000);
+
+pub static MATERIALIZE_NAPTIME : GucSetting<i32> = GucSetting::<i32>::new(0);
+
+pub static ZEROREC_INTERVAL : GucSetting<i32> = GucSetting::<i32>::new(0);
+
+pub static ZEROREC_MUTATIONS : GucSetting<i32> = GucSetting::<i32>::new(0);
+
+pub static WORKER_DATABASE : GucSetting<Option<&'static CStr>> =
+  GucSetting::<Option<&'static CStr>>::new(None);
+
+pub static SERVICE_SECRET : GucSetting<Option<&'static CStr>> =
+  GucSetting::<Option<&'static CStr>>::new(None);
+
+pub static TLS_CA_FILE : GucSetting<Option<&'static CStr>> =
+  GucSetting::<Option<&'static CStr>>::new(None);
+
+pub static TLS_CERT_KEY_FILE : GucSetting<Option<&'static CStr>> =
+  GucSetting::<Option<&'static CStr>>::new(None);
+
+pub static TLS_SERVER_NAME : GucSetting<Option<&'static CStr>> =
+  GucSetting::<Option<&'static CStr>>::new(None);
+
+//  Value of a string setting, `None` when not set or empty.
+pub fn string(setting : &GucSetting<Option<&'static CStr>>) -> Option<String> {
+  setting
+    .get()
+    .map(|s| s.to_string_lossy().into_owned())
+    .filter(|s| !s.is_empty())
+}
+
+pub fn service_secret() -> Option<Vec<u8>> {
+  string(&SERVICE_SECRET).map(|s| s.into_bytes())
+}
+
+pub fn init() {
+  GucRegistry::define_enum_guc(
+    "meritrank.version_check",
+    "What to do when the MeritRank service version is outside of the supported range.",
+    "error fails requests, warning only logs a warning, off skips the check.",
+    &VERSION_CHECK,
+    GucContext::Suset,
+    GucFlags::default(),
+  );
+
+  GucRegistry::define_int_guc(
+    "meritrank.log_min_duration",
+    "Log MeritRank requests that take longer than this, in milliseconds.",
+    "Zero logs all requests, -1 disables logging.",
+    &LOG_MIN_DURATION,
+    -1,
+    i32::MAX,
+    GucContext::Suset,
+    GucFlags::UNIT_MS,
+  );
+
+  GucRegistry::define_bool_guc(
+    "meritrank.log_requests",
+    "Log every MeritRank request with its decoded arguments.",
+    "Intended for debugging, produces a lot of output.",
+    &LOG_REQUESTS,
+    GucContext::Suset,
+    GucFlags::default(),
+  );
+
+  GucRegistry::define_bool_guc(
+    "meritrank.audit",
+    "Record graph mutations in mr_audit_log.",
+    "Covers mr_put_edge, mr_delete_edge, mr_delete_node, mr_reset and mr_create_context.",
+    &AUDIT,
+    GucContext::Suset,
+    GucFlags::default(),
+  );
+
+  GucRegistry::define_int_guc(
+    "meritrank.cache_size",
+    "Number of read responses cached by each backend.",
+    "Zero disables the cache.",
+    &CACHE_SIZE,
+    0,
+    i32::MAX,
+    GucContext::Userset,
+    GucFlags::default(),
+  );
+
+  GucRegistry::define_int_guc(
+    "meritrank.cache_ttl",
+    "Time after which cached responses expire, in milliseconds.",
+    "Zero keeps responses until invalidated or evicted.",
+    &CACHE_TTL,
+    0,
+    i32::MAX,
+    GucContext::Userset,
+    GucFlags::UNIT_MS,
+  );
+
+  GucRegistry::define_bool_guc(
+    "meritrank.synchronous_writes",
+    "Wait for the MeritRank service to apply graph mutations.",
+    "Applies to mr_put_edge, mr_delete_edge, mr_delete_node and mr_create_context called without the blocking argument.",
+    &SYNCHRONOUS_WRITES,
+    GucContext::Userset,
+    GucFlags::default(),
+  );
+
+  GucRegistry::define_bool_guc(
+    "meritrank.read_your_writes",
+    "Make reads wait until writes of the session to the context they read are applied by the service.",
+    "See mr_last_seq and mr_wait_for.",
+    &READ_YOUR_WRITES,
+    GucContext::Userset,
+    GucFlags::default(),
+  );
+
+  GucRegistry::define_int_guc(
+    "meritrank.shared_cache_size",
+    "Shared memory for the node score cache shared by all backends.",
+    "Zero disables the cache. Requires shared_preload_libraries.",
+    &SHARED_CACHE_SIZE,
+    0,
+    i32::MAX / 1024,
+    GucContext::Postmaster,
+    GucFlags::UNIT_KB,
+  );
+
+  GucRegistry::define_int_guc(
+    "meritrank.shared_cache_ttl",
+    "Time after which entries of the shared cache expire, in milliseconds.",
+    "Bounds how long other backends may see scores computed before a queued write was applied. Zero keeps entries until invalidated or evicted.",
+    &SHARED_CACHE_TTL,
+    0,
+    i32::MAX,
+    GucContext::Sighup,
+    GucFlags::UNIT_MS,
+  );
+
+  GucRegistry::define_int_guc(
+    "meritrank.materialize_naptime",
+    "Interval at which the background worker checks mr_materialize_schedule, in seconds.",
+    "Zero disables the worker. Requires shared_preload_libraries.",
+    &MATERIALIZE_NAPTIME,
+    0,
+    i32::MAX,
+    GucContext::Postmaster,
+    GucFlags::UNIT_S,
+  );
+
+  GucRegistry::define_int_guc(
+    "meritrank.zerorec_interval",
+    "Interval of scheduled zero recalculation, in seconds.",
+    "Zero disables the interval. The scheduler is started when this or meritrank.zerorec_mutations is set at server start.",
+    &ZEROREC_INTERVAL,
+    0,
+    i32::MAX,
+    GucContext::Sighup,
+    GucFlags::UNIT_S,
+  );
+
+  GucRegistry::define_int_guc(
+    "meritrank.zerorec_mutations",
+    "Number of mutations after which zero recalculation is scheduled.",
+    "Zero disables the threshold. Only mutations made through the connector are counted.",
+    &ZEROREC_MUTATIONS,
+    0,
+    i32::MAX,
+    GucContext::Sighup,
+    GucFlags::default(),
+  );
+
+  GucRegistry::define_string_guc(
+    "meritrank.worker_database",
+    "Database the MeritRank background workers connect to.",
+    "The postgres database is used when empty.",
+    &WORKER_DATABASE,
+    GucContext::Postmaster,
+    GucFlags::default(),
+  );
+
+  GucRegistry::define_string_guc(
+    "meritrank.service_secret",
+    "Shared secret used to sign requests to the MeritRank service.",
+    "Requests are sent unsigned when empty.",
+    &SERVICE_SECRET,
+    GucContext::Suset,
+    GucFlags::SUPERUSER_ONLY | GucFlags::NOT_IN_SAMPLE,
+  );
+
+  GucRegistry::define_string_guc(
+    "meritrank.tls_ca_file",
+    "CA certificates to verify the MeritRank service with, for tls+tcp:// URLs.",
+    "PEM file.",
+    &TLS_CA_FILE,
+    GucContext::Suset,
+    GucFlags::default(),
+  );
+
+  GucRegistry::define_string_guc(
+    "meritrank.tls_cert_key_file",
+    "Client certificate and private key to present to the MeritRank service, for tls+tcp:// URLs.",
+    "PEM file containing both the certificate and the key.",
+    &TLS_CERT_KEY_FILE,
+    GucContext::Suset,
+    GucFlags::default(),
+  );
+
+  GucRegistry::define_string_guc(
+    "meritrank.tls_server_name",
+    "Server name to verify the MeritRank service certificate against, for tls+tcp:// URLs.",
+    "The host from the service URL is used when empty.",
+    &TLS_SERVER_NAME,
+    GucContext::Suset,
+    GucFlags::default(),
+  );
+}
@@ -0,0 +1,144 @@
+#[cfg(any(test, feature = "pg_test"))]
+use lazy_static::lazy_static;
+use pgrx::*;
+use nng::Message;
+use std::error::Error;
+use std::time::Duration;
+#[cfg(any(test, feature = "pg_test"))]
+use std::sync::Mutex;
+
+use crate::guc;
+
+//  ================================================================
+//
+//    Request logging
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+lazy_static! {
+  static ref CAPTURED : Mutex<Option<Vec<String>>> = Mutex::new(None);
+}
+
+//  Returns lines logged by `f`, they are logged as usual too.
+#[cfg(any(test, feature = "pg_test"))]
+pub fn capture(f : impl FnOnce()) -> Vec<String> {
+  *CAPTURED.lock().unwrap() = Some(vec![]);
+  f();
+  CAPTURED.lock().unwrap().take().unwrap_or_default()
+}
+
+//  Renders the arguments of an encoded `Command` as text,
+//  e.g. `["U1", "U2", 1.0]`.
+fn decode_arguments(payload : &[u8]) -> String {
+  let command = match rmpv::decode::read_value(&mut &payload[..]) {
+    Ok(rmpv::Value::Array(x)) if x.len() == 4 => x,
+    _ => return "<malformed>".to_string(),
+  };
+
+  let args : Vec<u8> = match &command[3] {
+    rmpv::Value::Binary(x) => x.clone(),
+    rmpv::Value::Array(x)  => x.iter().filter_map(|b| b.as_u64()).map(|b| b as u8).collect(),
+    _                      => return "<malformed>".to_string(),
+  };
+
+  match rmpv::decode::read_value(&mut args.as_slice()) {
+    Ok(x)  => format!("{}", x),
+    Err(_) => "<malformed>".to_string(),
+  }
+}
+
+pub fn log_request(
+  payload  : &[u8],
+  response : &Result<Message, Box<dyn Error + 'static>>,
+  elapsed  : Duration,
+) {
+  let min_duration = guc::LOG_MIN_DURATION.get();
+  let log_requests = guc::LOG_REQUESTS.get();
+  let elapsed_msec = elapsed.as_secs_f64() * 1000.0;
+
+  if !log_requests && (min_duration < 0 || elapsed_msec < min_duration as f64) {
+    return;
+  }
+
+  let (id, context) = crate::command_header(payload);
+  let outcome       = match response {
+    Ok(msg) => format!("response: {} bytes", msg.len()),
+    Err(e)  => format!("error: {}", e),
+  };
+
+  let line = if log_requests {
+    format!(
+      "meritrank: duration: {:.3} ms  command: {}  context: \"{}\"  payload: {} bytes  {}  arguments: {}",
+      elapsed_msec, id, context, payload.len(), outcome, decode_arguments(payload)
+    )
+  } else {
+    format!(
+      "meritrank: duration: {:.3} ms  command: {}  context: \"{}\"  payload: {} bytes  {}",
+      elapsed_msec, id, context, payload.len(), outcome
+    )
+  };
+
+  #[cfg(any(test, feature = "pg_test"))]
+  if let Some(lines) = CAPTURED.lock().unwrap().as_mut() {
+    lines.push(line.clone());
+  }
+
+  log!("{}", line);
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use meritrank_service::protocol::*;
+
+  #[pg_test]
+  fn decode_arguments() {
+    let payload = encode_request(&Command {
+      id       : CMD_PUT_EDGE.to_string(),
+      context  : "X".to_string(),
+      blocking : false,
+      payload  : rmp_serde::to_vec(&("U1", "U2", 1.5)).unwrap()
+    }).unwrap();
+
+    assert_eq!(super::decode_arguments(&payload), "[\"U1\", \"U2\", 1.5]");
+    assert_eq!(super::decode_arguments(&[0xc1]), "<malformed>");
+  }
+
+  #[pg_test]
+  fn log_requests() {
+    let t = crate::testing::Scope::new();
+    let x = t.context("X");
+
+    let logged = || -> Vec<String> {
+      super::capture(|| { let _ = crate::mr_nodelist(Some(x.as_str()), None).unwrap(); })
+        .into_iter()
+        .filter(|line| line.contains(&format!("command: {} ", CMD_NODE_LIST)))
+        .collect()
+    };
+
+    assert_eq!(logged().len(), 0);
+
+    Spi::run("SET meritrank.log_min_duration = 0").unwrap();
+    let lines = logged();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with("meritrank: duration: "));
+    assert!(lines[0].contains(&format!("context: \"{}\"", x)));
+    assert!(!lines[0].contains("arguments:"));
+
+    Spi::run("SET meritrank.log_requests = on").unwrap();
+    let lines = logged();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].ends_with("arguments: nil"));
+
+    Spi::run("RESET meritrank.log_requests").unwrap();
+    Spi::run("RESET meritrank.log_min_duration").unwrap();
+  }
+}
@@ -0,0 +1,564 @@
+use pgrx::*;
+use pgrx::prelude::*;
+use pgrx::bgworkers::*;
+use pgrx::pgrx_sql_entity_graph::metadata::{
+  ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+use pgrx::pg_sys::panic::CaughtError;
+use std::error::Error;
+use std::panic::AssertUnwindSafe;
+use std::time::Duration;
+
+use crate::decode;
+use crate::guc;
+
+//  ================================================================
+//
+//    Materialized scores
+//
+//  ================================================================
+//
+//  Copies scores of the given egos into an ordinary table, so they
+//  can be queried with plain SQL. The target table needs at least
+//
+//    ego text, node text, score double precision, refreshed_at timestamptz
+//
+//  and, for incremental refreshes, a unique index on (ego, node).
+//
+//  A full refresh replaces all rows of each ego. An incremental one
+//  only touches rows whose score has changed, so `refreshed_at` is
+//  the time the score last changed.
+
+extension_sql!(r#"
+CREATE TABLE IF NOT EXISTS mr_materialize_schedule (
+  id          serial      PRIMARY KEY,
+  target      regclass    NOT NULL,
+  egos        text[],
+  egos_query  text,
+  context     text        NOT NULL DEFAULT '',
+  kind        text        NOT NULL DEFAULT '',
+  incremental boolean     NOT NULL DEFAULT false,
+  every       interval    NOT NULL DEFAULT '1 hour',
+  last_run    timestamptz,
+  last_rows   bigint,
+  last_error  text,
+  owner       regrole     NOT NULL DEFAULT current_user::regrole,
+  CHECK ((egos IS NULL) <> (egos_query IS NULL))
+);
+
+SELECT pg_catalog.pg_extension_config_dump('mr_materialize_schedule', '');
+SELECT pg_catalog.pg_extension_config_dump('mr_materialize_schedule_id_seq', '');
+"#,
+  name = "materialize_schedule",
+);
+
+//  SQL `regclass`, so the target can be given as a table name.
+pub struct RegClass(pg_sys::Oid);
+
+impl FromDatum for RegClass {
+  unsafe fn from_polymorphic_datum(datum : pg_sys::Datum, is_null : bool, typoid : pg_sys::Oid) -> Option<Self> {
+    pg_sys::Oid::from_polymorphic_datum(datum, is_null, typoid).map(RegClass)
+  }
+}
+
+unsafe impl SqlTranslatable for RegClass {
+  fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+    Ok(SqlMapping::literal("regclass"))
+  }
+
+  fn return_sql() -> Result<Returns, ReturnsError> {
+    Ok(Returns::One(SqlMapping::literal("regclass")))
+  }
+}
+
+//  Quoted, schema-qualified if needed, name of the table.
+fn table_name(target : &RegClass) -> Result<String, Box<dyn Error + 'static>> {
+  //  A cast of an OID without a table would give the bare number.
+  Spi::get_one_with_args::<String>(
+    "SELECT oid::regclass::text FROM pg_catalog.pg_class WHERE oid = $1",
+    vec![(PgBuiltInOids::OIDOID.oid(), target.0.into_datum())],
+  )?.ok_or_else(|| Box::from("target table not found"))
+}
+
+fn query_egos(query : &str) -> Result<Vec<String>, Box<dyn Error + 'static>> {
+  Spi::connect(|client| -> Result<Vec<String>, Box<dyn Error + 'static>> {
+    let mut egos = vec![];
+    for row in client.select(query, None, None)? {
+      if let Some(ego) = row.get::<String>(1)? {
+        egos.push(ego);
+      }
+    }
+    Ok(egos)
+  })
+}
+
+fn fetch_scores(
+  client  : &nng::Socket,
+  ego     : &str,
+  context : &str,
+  kind    : &str,
+) -> Result<decode::Edges, Box<dyn Error + 'static>> {
+  let payload = crate::scores_payload(
+    Some(context),
+    Some(ego),
+    Some(false),
+    Some(kind),
+    None, None,
+    None, None,
+    Some(0),
+    None
+  )?;
+
+  crate::request_on(Some(client), payload, Some(*crate::RECV_TIMEOUT_MSEC))
+}
+
+//  Returns the number of rows inserted or updated.
+fn store(
+  table       : &str,
+  ego         : &str,
+  scores      : decode::Edges,
+  incremental : bool,
+) -> Result<i64, Box<dyn Error + 'static>> {
+  let (nodes, scores) : (Vec<String>, Vec<f64>) =
+    scores.into_iter().map(|(_, node, score)| (node, score)).unzip();
+
+  let args = || vec![
+    (PgBuiltInOids::TEXTOID.oid(),        ego.into_datum()),
+    (PgBuiltInOids::TEXTARRAYOID.oid(),   nodes.clone().into_datum()),
+    (PgBuiltInOids::FLOAT8ARRAYOID.oid(), scores.clone().into_datum()),
+  ];
+
+  let rows = if incremental {
+    Spi::run_with_args(
+      &format!("DELETE FROM {} WHERE ego = $1 AND node <> ALL($2)", table),
+      Some(args()),
+    )?;
+    Spi::get_one_with_args::<i64>(
+      &format!(r#"
+        WITH written AS (
+          INSERT INTO {0} AS t (ego, node, score, refreshed_at)
+          SELECT $1, node, score, now() FROM unnest($2::text[], $3::float8[]) AS x (node, score)
+          ON CONFLICT (ego, node) DO UPDATE
+            SET score = excluded.score, refreshed_at = excluded.refreshed_at
+            WHERE t.score IS DISTINCT FROM excluded.score
+          RETURNING 1
+        )
+        SELECT count(*) FROM written
+      "#, table),
+      args(),
+    )?
+  } else {
+    Spi::run_with_args(
+      &format!("DELETE FROM {} WHERE ego = $1", table),
+      Some(args()[..1].to_vec()),
+    )?;
+    Spi::get_one_with_args::<i64>(
+      &format!(r#"
+        WITH written AS (
+          INSERT INTO {} (ego, node, score, refreshed_at)
+          SELECT $1, node, score, now() FROM unnest($2::text[], $3::float8[]) AS x (node, score)
+          RETURNING 1
+        )
+        SELECT count(*) FROM written
+      "#, table),
+      args(),
+    )?
+  };
+
+  return Ok(rows.unwrap_or(0));
+}
+
+fn materialize(
+  target      : &RegClass,
+  egos        : Vec<String>,
+  context     : &str,
+  kind        : &str,
+  incremental : bool,
+) -> Result<i64, Box<dyn Error + 'static>> {
+  crate::check_compatibility()?;
+
+  let table  = table_name(target)?;
+  let client = crate::dial(Some(*crate::RECV_TIMEOUT_MSEC))?;
+  let mut n  = 0;
+
+  for ego in egos.iter() {
+    let scores = fetch_scores(&client, ego, context, kind)?;
+    n += store(&table, ego, scores, incremental)?;
+  }
+
+  return Ok(n);
+}
+
+#[pg_extern]
+fn mr_materialize_scores(
+  target      : RegClass,
+  egos        : Vec<String>,
+  context     : default!(Option<&str>, "''"),
+  kind        : default!(Option<&str>, "''"),
+  incremental : default!(Option<bool>, "false"),
+) -> Result<i64, Box<dyn Error + 'static>> {
+  materialize(
+    &target,
+    egos,
+    context.unwrap_or(""),
+    kind.unwrap_or(""),
+    incremental.unwrap_or(false),
+  )
+}
+
+//  Same, for egos returned by a query.
+#[pg_extern(name = "mr_materialize_scores")]
+fn mr_materialize_scores_query(
+  target      : RegClass,
+  egos_query  : &str,
+  context     : default!(Option<&str>, "''"),
+  kind        : default!(Option<&str>, "''"),
+  incremental : default!(Option<bool>, "false"),
+) -> Result<i64, Box<dyn Error + 'static>> {
+  materialize(
+    &target,
+    query_egos(egos_query)?,
+    context.unwrap_or(""),
+    kind.unwrap_or(""),
+    incremental.unwrap_or(false),
+  )
+}
+
+//  ================================================================
+//
+//    Scheduled refreshes
+//
+//  ================================================================
+//
+//  With `meritrank.materialize_naptime` set, a background worker
+//  checks `mr_materialize_schedule` and refreshes every target whose
+//  `every` interval has passed since its last run.
+//
+//  The worker connects as the bootstrap superuser, so each schedule
+//  runs with the privileges of its `owner` role, the same way as a
+//  SECURITY DEFINER function owned by it would. Only superusers may
+//  write the schedule table.
+
+pub fn init() {
+  if unsafe { !pg_sys::process_shared_preload_libraries_in_progress } || guc::MATERIALIZE_NAPTIME.get() <= 0 {
+    return;
+  }
+
+  BackgroundWorkerBuilder::new("pgmer2 materialize")
+    .set_function("mr_materialize_worker_main")
+    .set_library("pgmer2")
+    .enable_spi_access()
+    .set_restart_time(Some(Duration::from_secs(10)))
+    .load();
+}
+
+type Schedule = (i32, pg_sys::Oid, pg_sys::Oid, Option<Vec<String>>, Option<String>, String, String, bool);
+
+fn due_schedules() -> Result<Vec<Schedule>, Box<dyn Error + 'static>> {
+  Spi::connect(|client| -> Result<Vec<Schedule>, Box<dyn Error + 'static>> {
+    let mut due = vec![];
+    let rows    = client.select(r#"
+      SELECT id, target::oid, owner::oid, egos, egos_query, context, kind, incremental
+        FROM mr_materialize_schedule
+       WHERE last_run IS NULL OR last_run + every <= now()
+       ORDER BY id
+    "#, None, None)?;
+
+    for row in rows {
+      due.push((
+        row.get::<i32>(1)?.unwrap_or(0),
+        row.get::<pg_sys::Oid>(2)?.unwrap_or(pg_sys::InvalidOid),
+        row.get::<pg_sys::Oid>(3)?.unwrap_or(pg_sys::InvalidOid),
+        row.get::<Vec<String>>(4)?,
+        row.get::<String>(5)?,
+        row.get::<String>(6)?.unwrap_or_default(),
+        row.get::<String>(7)?.unwrap_or_default(),
+        row.get::<bool>(8)?.unwrap_or(false),
+      ));
+    }
+    Ok(due)
+  })
+}
+
+//  Runs `f` with the privileges of `role`. The previous user is
+//  restored on return, and by Postgres itself if `f` raises an error.
+pub fn as_role<T>(
+  role : pg_sys::Oid,
+  f    : impl FnOnce() -> Result<T, Box<dyn Error + 'static>>,
+) -> Result<T, Box<dyn Error + 'static>> {
+  let exists = Spi::get_one_with_args::<bool>(
+    "SELECT EXISTS (SELECT FROM pg_catalog.pg_roles WHERE oid = $1)",
+    vec![(PgBuiltInOids::OIDOID.oid(), role.into_datum())],
+  )?.unwrap_or(false);
+
+  if !exists {
+    return Err(Box::from("owner role does not exist"));
+  }
+
+  let mut user    = pg_sys::InvalidOid;
+  let mut context : i32 = 0;
+
+  unsafe {
+    pg_sys::GetUserIdAndSecContext(&mut user, &mut context);
+    pg_sys::SetUserIdAndSecContext(role, context | pg_sys::SECURITY_LOCAL_USERID_CHANGE as i32);
+  }
+
+  let result = f();
+
+  unsafe { pg_sys::SetUserIdAndSecContext(user, context) };
+
+  return result;
+}
+
+//  Runs `f` in a subtransaction, so that an error, including one
+//  raised by Postgres, only rolls back what `f` has done.
+fn in_subtransaction<T>(
+  f : impl FnOnce() -> Result<T, Box<dyn Error + 'static>>,
+) -> Result<T, Box<dyn Error + 'static>> {
+  let memory = unsafe { pg_sys::CurrentMemoryContext };
+  let owner  = unsafe { pg_sys::CurrentResourceOwner };
+
+  unsafe {
+    pg_sys::BeginInternalSubTransaction(std::ptr::null());
+    pg_sys::MemoryContextSwitchTo(memory);
+  }
+
+  let result = PgTryBuilder::new(AssertUnwindSafe(f))
+    .catch_others(|e| Err(Box::from(match e {
+      CaughtError::PostgresError(report)     => report.message().to_string(),
+      CaughtError::ErrorReport(report)       => report.message().to_string(),
+      CaughtError::RustPanic { ereport, .. } => ereport.message().to_string(),
+    })))
+    .execute();
+
+  unsafe {
+    if result.is_ok() {
+      pg_sys::ReleaseCurrentSubTransaction();
+    } else {
+      pg_sys::RollbackAndReleaseCurrentSubTransaction();
+    }
+    pg_sys::MemoryContextSwitchTo(memory);
+    pg_sys::CurrentResourceOwner = owner;
+  }
+
+  return result;
+}
+
+//  Refreshes due schedules and records the outcome of each.
+//  Returns the number of schedules that ran.
+pub fn run_schedules() -> Result<usize, Box<dyn Error + 'static>> {
+  let due = due_schedules()?;
+
+  for (id, target, owner, egos, egos_query, context, kind, incremental) in due.iter() {
+    let result = in_subtransaction(|| as_role(*owner, || {
+      let egos = match (egos, egos_query) {
+        (Some(egos), _)     => egos.clone(),
+        (_, Some(query))    => query_egos(query)?,
+        _                   => return Err(Box::from("either egos or egos_query should be set")),
+      };
+      materialize(&RegClass(*target), egos, context, kind, *incremental)
+    }));
+
+    let (rows, error) = match result {
+      Ok(n)  => (Some(n), None),
+      Err(e) => (None,    Some(e.to_string())),
+    };
+
+    Spi::run_with_args(
+      "UPDATE mr_materialize_schedule SET last_run = now(), last_rows = $2, last_error = $3 WHERE id = $1",
+      Some(vec![
+        (PgBuiltInOids::INT4OID.oid(), (*id).into_datum()),
+        (PgBuiltInOids::INT8OID.oid(), rows.into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(), error.into_datum()),
+      ]),
+    )?;
+  }
+
+  return Ok(due.len());
+}
+
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn mr_materialize_worker_main(_arg : pg_sys::Datum) {
+  BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGHUP | SignalWakeFlags::SIGTERM);
+  BackgroundWorker::connect_worker_to_spi(guc::string(&guc::WORKER_DATABASE).as_deref().or(Some("postgres")), None);
+
+  let naptime = Duration::from_secs(guc::MATERIALIZE_NAPTIME.get() as u64);
+
+  while BackgroundWorker::wait_latch(Some(naptime)) {
+    //  Errors of a schedule are recorded in its row, only errors
+    //  reading or updating the table itself end up here.
+    let result = BackgroundWorker::transaction(|| run_schedules());
+
+    if let Err(e) = result {
+      warning!("materialize: {}", e);
+    }
+  }
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use crate::testing::Scope;
+
+  fn setup(t : &Scope, table : &str) -> String {
+    let x = t.context("X");
+
+    Spi::run(&format!(r#"
+      CREATE TEMP TABLE {} (
+        ego          text,
+        node         text,
+        score        double precision,
+        refreshed_at timestamptz,
+        UNIQUE (ego, node)
+      )
+    "#, table)).unwrap();
+
+    let _ = crate::mr_put_edge(Some(t.node("U1").as_str()), Some(t.node("U2").as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_put_edge(Some(t.node("U1").as_str()), Some(t.node("U3").as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+
+    return x;
+  }
+
+  fn count(query : &str, args : Vec<(pgrx::PgOid, Option<pgrx::pg_sys::Datum>)>) -> i64 {
+    Spi::get_one_with_args::<i64>(query, args).unwrap().unwrap()
+  }
+
+  #[pg_test]
+  fn materialize_scores() {
+    let t = Scope::new();
+    let x = setup(&t, "mr_test_scores");
+    let u1 = t.node("U1");
+
+    let materialize = |incremental : bool| count(
+      "SELECT mr_materialize_scores('mr_test_scores', ARRAY[$1], $2, '', $3)",
+      vec![
+        (PgBuiltInOids::TEXTOID.oid(), u1.clone().into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(), x.clone().into_datum()),
+        (PgBuiltInOids::BOOLOID.oid(), incremental.into_datum()),
+      ],
+    );
+
+    assert_eq!(materialize(false), 3);
+    assert_eq!(materialize(false), 3);
+    assert_eq!(count("SELECT count(*) FROM mr_test_scores", vec![]), 3);
+
+    //  Nothing has changed.
+    assert_eq!(materialize(true), 0);
+
+    let _ = crate::mr_delete_edge(Some(u1.as_str()), Some(t.node("U3").as_str()), Some(x.as_str()), None).unwrap();
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+
+    assert!(materialize(true) > 0);
+
+    //  Same rows as after a full refresh.
+    let n = count("SELECT count(*) FROM mr_test_scores", vec![]);
+    assert_eq!(materialize(false), n);
+  }
+
+  #[pg_test]
+  fn materialize_scores_query() {
+    let t = Scope::new();
+    let x = setup(&t, "mr_test_scores");
+
+    let n = count(
+      "SELECT mr_materialize_scores('mr_test_scores', format('SELECT %L UNION SELECT %L', $1, $2), $3)",
+      vec![
+        (PgBuiltInOids::TEXTOID.oid(), t.node("U1").into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(), t.node("U2").into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(), x.clone().into_datum()),
+      ],
+    );
+
+    assert_eq!(n, count("SELECT count(*) FROM mr_test_scores", vec![]));
+    assert_eq!(count("SELECT count(DISTINCT ego) FROM mr_test_scores", vec![]), 2);
+  }
+
+  #[pg_test]
+  fn materialize_schedule() {
+    let t = Scope::new();
+    let x = setup(&t, "mr_test_scores");
+
+    Spi::run_with_args(
+      "INSERT INTO mr_materialize_schedule (target, egos, context) VALUES ('mr_test_scores', ARRAY[$1], $2)",
+      Some(vec![
+        (PgBuiltInOids::TEXTOID.oid(), t.node("U1").into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(), x.clone().into_datum()),
+      ]),
+    ).unwrap();
+
+    assert_eq!(super::run_schedules().unwrap(), 1);
+
+    let (rows, error) = Spi::get_two::<i64, String>(
+      "SELECT last_rows, last_error FROM mr_materialize_schedule WHERE target = 'mr_test_scores'::regclass"
+    ).unwrap();
+
+    assert_eq!(rows,  Some(3));
+    assert_eq!(error, None);
+
+    let owner = Spi::get_one::<bool>(
+      "SELECT owner = current_user::regrole FROM mr_materialize_schedule WHERE target = 'mr_test_scores'::regclass"
+    ).unwrap();
+
+    assert_eq!(owner, Some(true));
+    assert_eq!(count("SELECT count(*) FROM mr_test_scores", vec![]), 3);
+
+    //  Not due again for an hour.
+    assert_eq!(super::run_schedules().unwrap(), 0);
+  }
+
+  #[pg_test]
+  fn materialize_schedule_errors() {
+    let t = Scope::new();
+    let x = setup(&t, "mr_test_scores");
+
+    Spi::run(r#"
+      CREATE TEMP TABLE mr_test_dropped (ego text, node text, score double precision, refreshed_at timestamptz);
+      DROP ROLE IF EXISTS mr_test_nobody;
+      CREATE ROLE mr_test_nobody NOLOGIN;
+    "#).unwrap();
+
+    let schedule = |target : &str, egos_query : Option<&str>, owner : Option<&str>| Spi::get_one_with_args::<i32>(
+      "INSERT INTO mr_materialize_schedule (target, egos, egos_query, context, owner) VALUES ($1::regclass, CASE WHEN $2 IS NULL THEN ARRAY[$3] END, $2, $4, coalesce($5, current_user)::regrole) RETURNING id",
+      vec![
+        (PgBuiltInOids::TEXTOID.oid(), target.into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(), egos_query.into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(), t.node("U1").into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(), x.clone().into_datum()),
+        (PgBuiltInOids::TEXTOID.oid(), owner.into_datum()),
+      ],
+    ).unwrap().unwrap();
+
+    let dropped = schedule("mr_test_dropped", None, None);
+    let invalid = schedule("mr_test_scores", Some("SELECT FROM nowhere"), None);
+    let denied  = schedule("mr_test_scores", None, Some("mr_test_nobody"));
+    let ok      = schedule("mr_test_scores", None, None);
+
+    Spi::run("DROP TABLE mr_test_dropped").unwrap();
+
+    assert_eq!(super::run_schedules().unwrap(), 4);
+
+    let outcome = |id : i32| Spi::get_two_with_args::<i64, String>(
+      "SELECT last_rows, last_error FROM mr_materialize_schedule WHERE id = $1 AND last_run IS NOT NULL",
+      vec![(PgBuiltInOids::INT4OID.oid(), id.into_datum())],
+    ).unwrap();
+
+    assert_eq!(outcome(dropped).1.as_deref(), Some("target table not found"));
+    assert!(outcome(invalid).1.unwrap().contains("nowhere"));
+    assert!(outcome(denied).1.unwrap().contains("permission denied"));
+
+    //  Failures of other schedules do not roll back this one.
+    assert_eq!(outcome(ok), (Some(3), None));
+    assert_eq!(count("SELECT count(*) FROM mr_test_scores", vec![]), 3);
+
+    Spi::run("DELETE FROM mr_materialize_schedule WHERE owner = 'mr_test_nobody'::regrole; DROP ROLE mr_test_nobody").unwrap();
+  }
+}
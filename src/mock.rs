@@ -0,0 +1,671 @@
+use lazy_static::lazy_static;
+use meritrank_service::protocol::*;
+use nng::*;
+#[cfg(feature = "tls")]
+use nng::options::Options;
+#[cfg(feature = "tls")]
+use nng::options::transport::tls::CertKeyFile;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use pgrx::{pg_guard, pg_sys};
+
+use crate::auth;
+
+//  ================================================================
+//
+//    In-process mock of the MeritRank service
+//
+//  ================================================================
+//
+//  Understands the `Command` envelope and keeps an in-memory graph
+//  per context. Scores are expected visits of random walks from the
+//  ego, computed exactly instead of sampled, so they are deterministic
+//  and close to what the real service returns.
+
+pub const MOCK_VERSION : &str = "0.3.15";
+
+//  Probability of a walk to continue on each step.
+const ALPHA : f64 = 0.85;
+
+#[derive(Clone, Default)]
+pub struct Faults {
+  //  Sleep before replying.
+  pub delay_msec : u64,
+  //  Reply with bytes that are not valid MessagePack.
+  pub garbage    : bool,
+  //  Close the connection instead of replying.
+  pub disconnect : bool,
+}
+
+//  Edge weight and whether the edge was inherited from the null
+//  context when the context was created.
+type Edges = BTreeMap<(String, String), (f64, bool)>;
+
+#[derive(Default)]
+struct Graph {
+  contexts : BTreeMap<String, Edges>,
+  filters  : HashMap<String, BTreeSet<String>>,
+}
+
+#[derive(Default)]
+struct State {
+  graph   : Graph,
+  faults  : Faults,
+  secret  : Option<Vec<u8>>,
+  //  Reported instead of `MOCK_VERSION`.
+  version : Option<String>,
+  //  Requests received, by command.
+  counts  : HashMap<String, u64>,
+  //  Time to apply writes queued to each context, which a sync of
+  //  that context, or of all contexts, waits for.
+  backlog : HashMap<String, u64>,
+}
+
+pub struct MockService {
+  url    : String,
+  state  : Arc<Mutex<State>>,
+  socket : Socket,
+  thread : Option<JoinHandle<()>>,
+}
+
+fn is_user(node : &str) -> bool {
+  node.starts_with('U')
+}
+
+impl Graph {
+  //  New contexts start with user-to-user edges of the null context.
+  fn context_mut(&mut self, context : &str) -> &mut Edges {
+    if !self.contexts.contains_key(context) {
+      let inherited : Edges = match self.contexts.get("") {
+        Some(null) if context != "" =>
+          null
+            .iter()
+            .filter(|((src, dst), _)| is_user(src) && is_user(dst))
+            .map(|(k, (w, _))| (k.clone(), (*w, true)))
+            .collect(),
+        _ => Edges::new(),
+      };
+      self.contexts.insert(context.to_string(), inherited);
+    }
+    return self.contexts.get_mut(context).unwrap();
+  }
+
+  //  The null context is the sum of all contexts.
+  fn view(&self, context : &str) -> BTreeMap<(String, String), f64> {
+    let mut edges = BTreeMap::new();
+
+    if context != "" {
+      if let Some(x) = self.contexts.get(context) {
+        for (k, (w, _)) in x.iter() {
+          edges.insert(k.clone(), *w);
+        }
+      }
+      return edges;
+    }
+
+    for x in self.contexts.values() {
+      for (k, (w, inherited)) in x.iter() {
+        if !inherited {
+          *edges.entry(k.clone()).or_insert(0.0) += w;
+        }
+      }
+    }
+    return edges;
+  }
+
+  fn delete_node(&mut self, context : &str, node : &str) {
+    for (name, edges) in self.contexts.iter_mut() {
+      if context == "" || name == context {
+        edges.retain(|(src, dst), _| src != node && dst != node);
+      }
+    }
+  }
+}
+
+fn walk(edges : &BTreeMap<(String, String), f64>, ego : &str) -> BTreeMap<String, f64> {
+  let mut out : HashMap<&str, Vec<(&str, f64)>> = HashMap::new();
+  for ((src, dst), w) in edges.iter() {
+    out.entry(src.as_str()).or_default().push((dst.as_str(), *w));
+  }
+
+  let mut visits   : BTreeMap<String, f64> = BTreeMap::new();
+  let mut negative : BTreeMap<String, f64> = BTreeMap::new();
+  let mut frontier : HashMap<&str, f64>    = HashMap::new();
+
+  visits.insert(ego.to_string(), 1.0);
+  frontier.insert(ego, 1.0);
+
+  for _ in 0..1000 {
+    let mut next : HashMap<&str, f64> = HashMap::new();
+
+    for (node, mass) in frontier.iter() {
+      let targets = match out.get(node) {
+        Some(x) => x,
+        None    => continue,
+      };
+      let total : f64 = targets.iter().map(|(_, w)| w.abs()).sum();
+      if total == 0.0 {
+        continue;
+      }
+      for (dst, w) in targets.iter() {
+        let p = mass * ALPHA * w.abs() / total;
+        if *w > 0.0 {
+          *next.entry(*dst).or_insert(0.0) += p;
+        } else if *w < 0.0 {
+          //  Negative hits end the walk.
+          *negative.entry(dst.to_string()).or_insert(0.0) += p;
+        }
+      }
+    }
+
+    if next.values().sum::<f64>() < 1e-12 {
+      break;
+    }
+    for (node, p) in next.iter() {
+      *visits.entry(node.to_string()).or_insert(0.0) += p;
+    }
+    frontier = next;
+  }
+
+  let total : f64 = visits.values().sum();
+
+  let mut scores = BTreeMap::new();
+  for (node, v) in visits.iter() {
+    scores.insert(node.clone(), v / total);
+  }
+  for (node, v) in negative.iter() {
+    *scores.entry(node.clone()).or_insert(0.0) -= v / total;
+  }
+  return scores;
+}
+
+fn reachable(
+  edges   : &BTreeMap<(String, String), f64>,
+  start   : &str,
+  forward : bool,
+) -> BTreeSet<String> {
+  let mut adjacent : HashMap<&str, Vec<&str>> = HashMap::new();
+  for (src, dst) in edges.keys() {
+    if forward {
+      adjacent.entry(src.as_str()).or_default().push(dst.as_str());
+    } else {
+      adjacent.entry(dst.as_str()).or_default().push(src.as_str());
+    }
+  }
+
+  let mut seen  = BTreeSet::from([start.to_string()]);
+  let mut stack = vec![start];
+  while let Some(node) = stack.pop() {
+    for next in adjacent.get(node).into_iter().flatten() {
+      if seen.insert(next.to_string()) {
+        stack.push(*next);
+      }
+    }
+  }
+  return seen;
+}
+
+fn page<T>(items : Vec<T>, index : u32, count : u32) -> Vec<T> {
+  items.into_iter().skip(index as usize).take(count as usize).collect()
+}
+
+fn respond<T : Serialize>(value : &T) -> Result<Vec<u8>, Box<dyn Error + 'static>> {
+  encode_response(value).map_err(|e| format!("{:?}", e).into())
+}
+
+fn handle(graph : &mut Graph, command : &Command) -> Result<Vec<u8>, Box<dyn Error + 'static>> {
+  let context = command.context.as_str();
+  let args    = command.payload.as_slice();
+
+  match command.id.as_str() {
+    CMD_VERSION => {
+      Ok(rmp_serde::to_vec(MOCK_VERSION)?)
+    },
+
+    CMD_LOG_LEVEL | CMD_SYNC | CMD_RECALCULATE_ZERO => {
+      respond(&())
+    },
+
+    CMD_RESET => {
+      *graph = Graph::default();
+      respond(&())
+    },
+
+    CMD_CREATE_CONTEXT => {
+      let _ = graph.context_mut(context);
+      respond(&())
+    },
+
+    CMD_PUT_EDGE => {
+      let (src, dst, weight) : (String, String, f64) = rmp_serde::from_slice(args)?;
+      graph.context_mut(context).insert((src, dst), (weight, false));
+      respond(&())
+    },
+
+    CMD_DELETE_EDGE => {
+      let (src, dst) : (String, String) = rmp_serde::from_slice(args)?;
+      graph.context_mut(context).remove(&(src, dst));
+      respond(&())
+    },
+
+    CMD_DELETE_NODE => {
+      let node : String = rmp_serde::from_slice(args)?;
+      graph.delete_node(context, &node);
+      respond(&())
+    },
+
+    CMD_NODE_SCORE => {
+      let (ego, dst) : (String, String) = rmp_serde::from_slice(args)?;
+      let score = walk(&graph.view(context), &ego).get(&dst).copied().unwrap_or(0.0);
+      respond(&vec![(ego, dst, score)])
+    },
+
+    CMD_SCORES => {
+      let (ego, kind, hide_personal, lt, lte, gt, gte, index, count) :
+        (String, String, bool, f64, bool, f64, bool, u32, u32) = rmp_serde::from_slice(args)?;
+
+      let edges = graph.view(context);
+
+      let mut scores : Vec<(String, String, f64)> =
+        walk(&edges, &ego)
+          .into_iter()
+          .filter(|(node, _)| node.starts_with(kind.as_str()))
+          .filter(|(node, _)| !hide_personal || is_user(node) || !edges.contains_key(&(node.clone(), ego.clone())))
+          .filter(|(_, s)| if lte { *s <= lt } else { *s < lt })
+          .filter(|(_, s)| if gte { *s >= gt } else { *s > gt })
+          .map(|(node, s)| (ego.clone(), node, s))
+          .collect();
+
+      scores.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.1.cmp(&b.1)));
+      respond(&page(scores, index, count))
+    },
+
+    CMD_GRAPH => {
+      let (ego, focus, positive_only, index, count) :
+        (String, String, bool, u32, u32) = rmp_serde::from_slice(args)?;
+
+      let edges : BTreeMap<(String, String), f64> =
+        graph
+          .view(context)
+          .into_iter()
+          .filter(|(_, w)| !positive_only || *w > 0.0)
+          .collect();
+
+      //  Edges between users on paths from the ego to the focus.
+      let from_ego = reachable(&edges, &ego,   true);
+      let to_focus = reachable(&edges, &focus, false);
+      let on_path  = |node : &str| {
+        from_ego.contains(node) &&
+        to_focus.contains(node) &&
+        (is_user(node) || node == ego || node == focus)
+      };
+
+      let result : Vec<(String, String, f64)> =
+        edges
+          .into_iter()
+          .filter(|((src, dst), _)| on_path(src) && on_path(dst))
+          .map(|((src, dst), w)| (src, dst, w))
+          .collect();
+
+      respond(&page(result, index, count))
+    },
+
+    CMD_NODE_LIST => {
+      let nodes : BTreeSet<String> =
+        graph
+          .view(context)
+          .into_keys()
+          .flat_map(|(src, dst)| [src, dst])
+          .collect();
+      respond(&nodes.into_iter().map(|x| (x,)).collect::<Vec<(String,)>>())
+    },
+
+    CMD_EDGES => {
+      respond(&graph
+        .view(context)
+        .into_iter()
+        .map(|((src, dst), w)| (src, dst, w))
+        .collect::<Vec<(String, String, f64)>>())
+    },
+
+    CMD_CONNECTED => {
+      let ego : String = rmp_serde::from_slice(args)?;
+      respond(&graph
+        .view(context)
+        .into_keys()
+        .filter(|(src, _)| *src == ego)
+        .collect::<Vec<(String, String)>>())
+    },
+
+    CMD_MUTUAL_SCORES => {
+      let ego : String = rmp_serde::from_slice(args)?;
+      let edges        = graph.view(context);
+
+      let result : Vec<(String, f64, f64)> =
+        walk(&edges, &ego)
+          .into_iter()
+          .filter(|(node, s)| is_user(node) && *s > 0.0)
+          .map(|(node, s)| {
+            let back = walk(&edges, &node).get(&ego).copied().unwrap_or(0.0);
+            (node, s, back)
+          })
+          .collect();
+
+      respond(&result)
+    },
+
+    CMD_READ_NEW_EDGES_FILTER => {
+      let src : String = rmp_serde::from_slice(args)?;
+      let seen : Vec<String> =
+        graph.filters.get(&src).into_iter().flatten().cloned().collect();
+      respond(&rmp_serde::to_vec(&seen)?)
+    },
+
+    CMD_WRITE_NEW_EDGES_FILTER => {
+      let (src, filter) : (String, Vec<u8>) = rmp_serde::from_slice(args)?;
+      let seen : Vec<String> = rmp_serde::from_slice(&filter)?;
+      graph.filters.insert(src, seen.into_iter().collect());
+      respond(&())
+    },
+
+    CMD_FETCH_NEW_EDGES => {
+      let (src, prefix) : (String, String) = rmp_serde::from_slice(args)?;
+      let scores = walk(&graph.view(""), &src);
+      let seen   = graph.filters.entry(src).or_default();
+
+      let result : Vec<(String, f64)> =
+        scores
+          .into_iter()
+          .filter(|(node, s)| node.starts_with(prefix.as_str()) && *s > 0.0)
+          .filter(|(node, _)| seen.insert(node.clone()))
+          .collect();
+
+      respond(&result)
+    },
+
+    other => Err(format!("unknown command: {}", other).into()),
+  }
+}
+
+//  Returns the reply, and the time to wait before sending it.
+fn process(state : &Mutex<State>, request : &[u8]) -> (Vec<u8>, u64) {
+  let mut state = state.lock().unwrap();
+
+  let payload = match &state.secret {
+    Some(secret) => match auth::verify(secret, request) {
+      Some(x) => x,
+      None    => return (b"unauthorized".to_vec(), 0),
+    },
+    None => request.to_vec(),
+  };
+
+  let command : Command = match rmp_serde::from_slice(&payload) {
+    Ok(x)  => x,
+    Err(e) => return (rmp_serde::to_vec(&format!("{}", e)).unwrap_or_default(), 0),
+  };
+
+  *state.counts.entry(command.id.clone()).or_default() += 1;
+
+  if let (CMD_VERSION, Some(version)) = (command.id.as_str(), &state.version) {
+    return (rmp_serde::to_vec(version).unwrap_or_default(), 0);
+  }
+
+  let wait = match (command.id.as_str(), command.context.as_str()) {
+    (CMD_SYNC, "")      => state.backlog.values().copied().max().unwrap_or(0),
+    (CMD_SYNC, context) => state.backlog.get(context).copied().unwrap_or(0),
+    _                   => 0,
+  };
+
+  let reply = match handle(&mut state.graph, &command) {
+    Ok(x)  => x,
+    Err(e) => rmp_serde::to_vec(&format!("{}", e)).unwrap_or_default(),
+  };
+
+  return (reply, wait);
+}
+
+fn serve(socket : Socket, state : Arc<Mutex<State>>) {
+  while let Ok(msg) = socket.recv() {
+    let faults = state.lock().unwrap().faults.clone();
+
+    if faults.delay_msec > 0 {
+      thread::sleep(Duration::from_millis(faults.delay_msec));
+    }
+
+    if faults.disconnect {
+      if let Some(pipe) = msg.pipe() {
+        pipe.close();
+      }
+      continue;
+    }
+
+    let reply = match faults.garbage {
+      true  => vec![0xc1, 0xc1, 0xc1],
+      false => {
+        let (reply, wait) = process(&state, msg.as_slice());
+        thread::sleep(Duration::from_millis(wait));
+        reply
+      },
+    };
+
+    if socket.send(Message::from(reply.as_slice())).is_err() {
+      break;
+    }
+  }
+}
+
+impl MockService {
+  pub fn start(url : &str) -> Result<MockService, Box<dyn Error + 'static>> {
+    let socket = Socket::new(Protocol::Rep0)?;
+    socket.listen(url)?;
+    return Ok(MockService::spawn(url, socket));
+  }
+
+  #[cfg(feature = "tls")]
+  pub fn start_tls(url : &str, cert_key_file : &str) -> Result<MockService, Box<dyn Error + 'static>> {
+    let socket   = Socket::new(Protocol::Rep0)?;
+    let listener = ListenerBuilder::new(&socket, url)?;
+    listener.set_opt::<CertKeyFile>(cert_key_file.to_string())?;
+    let _ = listener.start().map_err(|(_, err)| err)?;
+    return Ok(MockService::spawn(url, socket));
+  }
+
+  fn spawn(url : &str, socket : Socket) -> MockService {
+    let state    = Arc::new(Mutex::new(State::default()));
+    let shared   = state.clone();
+    let listener = socket.clone();
+    let thread   = thread::spawn(move || serve(listener, shared));
+
+    return MockService {
+      url    : url.to_string(),
+      state,
+      socket,
+      thread : Some(thread),
+    };
+  }
+
+  pub fn url(&self) -> &str {
+    &self.url
+  }
+
+  pub fn set_faults(&self, faults : Faults) {
+    self.state.lock().unwrap().faults = faults;
+  }
+
+  pub fn set_version(&self, version : &str) {
+    self.state.lock().unwrap().version = Some(version.to_string());
+  }
+
+  //  Make syncs of the context, and of all contexts, take `msec`.
+  pub fn set_backlog(&self, context : &str, msec : u64) {
+    self.state.lock().unwrap().backlog.insert(context.to_string(), msec);
+  }
+
+  //  Number of requests with the command received so far.
+  pub fn count(&self, id : &str) -> u64 {
+    self.state.lock().unwrap().counts.get(id).copied().unwrap_or(0)
+  }
+
+  //  Reject requests that are not signed with `secret`.
+  pub fn require_secret(&self, secret : &[u8]) {
+    self.state.lock().unwrap().secret = Some(secret.to_vec());
+  }
+}
+
+//  Closing the socket makes `serve` return, so the thread and the
+//  listener go away with the service.
+impl Drop for MockService {
+  fn drop(&mut self) {
+    self.socket.close();
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+lazy_static! {
+  static ref DEFAULT_SERVICE : Mutex<Option<MockService>> = Mutex::new(None);
+}
+
+#[pg_guard]
+extern "C" fn stop_default(_code : i32, _arg : pg_sys::Datum) {
+  let _ = DEFAULT_SERVICE.lock().map(|mut x| x.take());
+}
+
+//  URL of the mock service of this backend, started on first use and
+//  stopped when the backend exits. Only test builds have it, when
+//  `MERITRANK_SERVICE_URL` is not set.
+pub fn default_url() -> String {
+  let mut service = DEFAULT_SERVICE.lock().unwrap();
+  if service.is_none() {
+    let url = format!("ipc:///tmp/pgmer2-mock-{}", std::process::id());
+    *service = Some(MockService::start(&url).expect("failed to start the mock service"));
+    unsafe { pg_sys::on_proc_exit(Some(stop_default), pg_sys::Datum::from(0)) };
+  }
+  return service.as_ref().unwrap().url().to_string();
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use super::*;
+
+  fn start(name : &str) -> MockService {
+    let url     = format!("ipc:///tmp/pgmer2-mock-{}-{}", name, std::process::id());
+    let service = MockService::start(&url).unwrap();
+    crate::testing::shared();
+    crate::set_service_url(Some(url));
+    //  Pass the version check before injecting faults.
+    let _ = crate::mr_nodelist(None, None).unwrap();
+    return service;
+  }
+
+  #[pg_test]
+  fn mock_delay() {
+    let service = start("delay");
+
+    service.set_faults(Faults { delay_msec : 500, ..Faults::default() });
+    assert!(crate::mr_sync(Some(100)).is_err());
+
+    service.set_faults(Faults::default());
+    crate::set_service_url(None);
+  }
+
+  #[pg_test]
+  fn mock_try_sync() {
+    let service = start("try-sync");
+
+    service.set_faults(Faults { delay_msec : 500, ..Faults::default() });
+    assert_eq!(crate::mr_try_sync(Some("X"), Some(100)).unwrap(), false);
+
+    service.set_faults(Faults::default());
+    assert_eq!(crate::mr_try_sync(Some("X"), Some(1000)).unwrap(), true);
+    assert!(crate::mr_sync_context(Some("X"), Some(1000)).is_ok());
+    crate::set_service_url(None);
+  }
+
+  #[pg_test]
+  fn mock_sync_context() {
+    let service = start("sync-context");
+
+    service.set_backlog("Y", 1000);
+
+    //  A sync of X doesn't wait for writes queued to Y.
+    assert_eq!(crate::mr_try_sync(Some("X"), Some(500)).unwrap(), true);
+
+    //  A sync of all contexts, or of Y, does.
+    assert_eq!(crate::mr_try_sync(Some(""), Some(200)).unwrap(), false);
+    service.set_backlog("Y", 0);
+    assert_eq!(crate::mr_try_sync(Some("Y"), Some(5000)).unwrap(), true);
+
+    assert_eq!(service.count(CMD_SYNC), 3);
+    crate::set_service_url(None);
+  }
+
+  #[pg_test]
+  fn mock_drop() {
+    let url = format!("ipc:///tmp/pgmer2-mock-drop-{}", std::process::id());
+
+    //  The address is free again once the service is dropped.
+    drop(MockService::start(&url).unwrap());
+    drop(MockService::start(&url).unwrap());
+  }
+
+  #[pg_test]
+  fn mock_incompatible() {
+    let url     = format!("ipc:///tmp/pgmer2-mock-incompatible-{}", std::process::id());
+    let service = MockService::start(&url).unwrap();
+    service.set_version("0.1.0");
+    crate::testing::shared();
+    crate::set_service_url(Some(url));
+
+    assert!(crate::mr_nodelist(None, None).is_err());
+    assert!(crate::mr_nodelist(None, None).is_err());
+
+    //  The failed check is not repeated, and no request goes through.
+    assert_eq!(service.count(CMD_VERSION),   1);
+    assert_eq!(service.count(CMD_NODE_LIST), 0);
+
+    //  Requests go through with the check turned off.
+    Spi::run("SET meritrank.version_check = 'off'").unwrap();
+    assert!(crate::mr_nodelist(None, None).is_ok());
+    Spi::run("RESET meritrank.version_check").unwrap();
+
+    crate::set_service_url(None);
+  }
+
+  #[pg_test]
+  fn mock_garbage() {
+    let service = start("garbage");
+
+    service.set_faults(Faults { garbage : true, ..Faults::default() });
+    assert!(crate::mr_nodelist(None, None).is_err());
+
+    service.set_faults(Faults::default());
+    assert!(crate::mr_nodelist(None, None).is_ok());
+    crate::set_service_url(None);
+  }
+
+  #[pg_test]
+  fn mock_disconnect() {
+    let service = start("disconnect");
+
+    service.set_faults(Faults { disconnect : true, ..Faults::default() });
+    assert!(crate::mr_sync(Some(200)).is_err());
+
+    service.set_faults(Faults::default());
+    crate::set_service_url(None);
+  }
+}
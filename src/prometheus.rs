@@ -0,0 +1,117 @@
+use pgrx::*;
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::SUPPORTED_COMMANDS;
+use crate::stats::{self, ERROR_CLASSES, LATENCY_BUCKETS_MSEC};
+
+//  ================================================================
+//
+//    Prometheus text exposition format
+//
+//  ================================================================
+
+fn escape_label(s : &str) -> String {
+  s.replace('\\', "\\\\")
+   .replace('"',  "\\\"")
+   .replace('\n', "\\n")
+}
+
+fn header(out : &mut String, name : &str, kind : &str, help : &str) {
+  let _ = writeln!(out, "# HELP {} {}", name, help);
+  let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+pub fn render(stats : &stats::RequestStats) -> String {
+  let mut out = String::new();
+
+  let commands : Vec<(String, &stats::CommandStats)> =
+    SUPPORTED_COMMANDS
+      .iter()
+      .map(|id| escape_label(id))
+      .zip(stats.commands.iter())
+      .collect();
+
+  header(&mut out, "meritrank_requests_total", "counter", "Requests sent to the MeritRank service.");
+  for (id, x) in commands.iter() {
+    let _ = writeln!(out, "meritrank_requests_total{{command=\"{}\"}} {}", id, x.calls);
+  }
+
+  header(&mut out, "meritrank_request_errors_total", "counter", "Failed requests to the MeritRank service.");
+  for (id, x) in commands.iter() {
+    let _ = writeln!(out, "meritrank_request_errors_total{{command=\"{}\"}} {}", id, x.errors);
+  }
+
+  header(&mut out, "meritrank_request_duration_seconds", "histogram", "Round-trip time of requests to the MeritRank service.");
+  for (id, x) in commands.iter() {
+    let mut cumulative = 0;
+    for (bound, count) in LATENCY_BUCKETS_MSEC.iter().zip(x.buckets.iter()) {
+      cumulative += count;
+      let _ = writeln!(
+        out,
+        "meritrank_request_duration_seconds_bucket{{command=\"{}\",le=\"{}\"}} {}",
+        id,
+        *bound as f64 / 1000.0,
+        cumulative
+      );
+    }
+    let _ = writeln!(out, "meritrank_request_duration_seconds_bucket{{command=\"{}\",le=\"+Inf\"}} {}", id, x.calls);
+    let _ = writeln!(out, "meritrank_request_duration_seconds_sum{{command=\"{}\"}} {}", id, x.total_usec as f64 / 1000000.0);
+    let _ = writeln!(out, "meritrank_request_duration_seconds_count{{command=\"{}\"}} {}", id, x.calls);
+  }
+
+  header(&mut out, "meritrank_request_bytes_sent_total", "counter", "Bytes sent to the MeritRank service.");
+  for (id, x) in commands.iter() {
+    let _ = writeln!(out, "meritrank_request_bytes_sent_total{{command=\"{}\"}} {}", id, x.bytes_sent);
+  }
+
+  header(&mut out, "meritrank_request_bytes_received_total", "counter", "Bytes received from the MeritRank service.");
+  for (id, x) in commands.iter() {
+    let _ = writeln!(out, "meritrank_request_bytes_received_total{{command=\"{}\"}} {}", id, x.bytes_recv);
+  }
+
+  header(&mut out, "meritrank_errors_total", "counter", "Connector errors by class.");
+  for (class, count) in ERROR_CLASSES.iter().zip(stats.errors.iter()) {
+    let _ = writeln!(out, "meritrank_errors_total{{class=\"{}\"}} {}", class, count);
+  }
+
+  header(&mut out, "meritrank_dials_total", "counter", "Connections dialed to the MeritRank service.");
+  let _ = writeln!(out, "meritrank_dials_total {}", stats.dials);
+
+  header(&mut out, "meritrank_dial_errors_total", "counter", "Failed connection attempts to the MeritRank service.");
+  let _ = writeln!(out, "meritrank_dial_errors_total {}", stats.dial_errors);
+
+  return out;
+}
+
+#[pg_extern]
+fn mr_metrics_prometheus() -> Result<String, Box<dyn Error + 'static>> {
+  return Ok(render(&stats::snapshot()?));
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use meritrank_service::protocol::*;
+
+  #[pg_test]
+  fn metrics_prometheus() {
+    crate::testing::exclusive();
+    let _ = crate::stats::mr_stat_reset().unwrap();
+    let _ = crate::mr_nodelist(None, None).unwrap();
+
+    let text = crate::prometheus::mr_metrics_prometheus().unwrap();
+
+    assert!(text.contains("# TYPE meritrank_request_duration_seconds histogram"));
+    assert!(text.contains(&format!("meritrank_requests_total{{command=\"{}\"}} 1", CMD_NODE_LIST)));
+    assert!(text.contains(&format!("meritrank_request_duration_seconds_bucket{{command=\"{}\",le=\"+Inf\"}} 1", CMD_NODE_LIST)));
+    assert!(text.contains("meritrank_errors_total{class=\"timeout\"} 0"));
+  }
+}
@@ -0,0 +1,432 @@
+use lazy_static::lazy_static;
+use pgrx::*;
+use pgrx::prelude::*;
+use pgrx::lwlock::PgLwLock;
+use pgrx::shmem::*;
+use meritrank_service::protocol::*;
+use std::error::Error;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+
+use crate::{cache, guc, shared_cache};
+
+//  ================================================================
+//
+//    Write sequence numbers
+//
+//  ================================================================
+//
+//  Writes are sent with `blocking : false`, the service queues them
+//  and applies them in order. Each acknowledged write gets the next
+//  sequence number. The service doesn't report which writes it has
+//  applied, but once a sync sent after write N returns, all writes
+//  up to N are applied. The highest such N is the applied watermark.
+//
+//  Numbers are issued after the service has acknowledged the write,
+//  so a sync that sees the number was sent after the write was queued.
+//
+//  With shared memory the numbers are shared by all backends, so
+//  a sync by any backend advances the watermark for everyone.
+//
+//  Since the service doesn't report applied numbers either, reads
+//  with `meritrank.read_your_writes` can't wait for exactly the
+//  session's last write. They sync the context being read, which
+//  waits for every write queued to it, including those of other
+//  sessions. Reads of the empty context, and reads after writes to
+//  it, sync all contexts. Reads of contexts the session hasn't
+//  written to since its last sync don't wait at all.
+//
+//  The context of each recently issued number is kept too, so that
+//  waiting for a number syncs only its context. Older numbers, and
+//  numbers of other backends without shared memory, sync all contexts.
+
+const WRITES : &[&str] = &[
+  CMD_RESET,
+  CMD_RECALCULATE_ZERO,
+  CMD_CREATE_CONTEXT,
+  CMD_PUT_EDGE,
+  CMD_DELETE_EDGE,
+  CMD_DELETE_NODE,
+];
+
+const RECENT         : usize = 256;
+const RECENT_CONTEXT : usize = 64;
+
+#[derive(Copy, Clone)]
+pub struct Issued {
+  seq     : i64,
+  pid     : i32,
+  //  More than `RECENT_CONTEXT` if the context didn't fit.
+  len     : usize,
+  context : [u8; RECENT_CONTEXT],
+}
+
+#[derive(Copy, Clone)]
+pub struct Watermark {
+  pub issued  : i64,
+  pub applied : i64,
+  recent      : [Issued; RECENT],
+}
+
+impl Default for Watermark {
+  fn default() -> Watermark {
+    let issued = Issued {
+      seq     : 0,
+      pid     : 0,
+      len     : 0,
+      context : [0; RECENT_CONTEXT],
+    };
+    Watermark {
+      issued  : 0,
+      applied : 0,
+      recent  : [issued; RECENT],
+    }
+  }
+}
+
+impl Watermark {
+  fn issue(&mut self, context : &str) -> i64 {
+    self.issued += 1;
+
+    let bytes = context.as_bytes();
+    let entry = &mut self.recent[self.issued as usize % RECENT];
+    entry.seq = self.issued;
+    entry.pid = unsafe { pg_sys::MyProcPid };
+    entry.len = bytes.len();
+    if bytes.len() <= RECENT_CONTEXT {
+      entry.context[..bytes.len()].copy_from_slice(bytes);
+    }
+
+    return self.issued;
+  }
+
+  //  Context and backend of a recently issued number.
+  fn context_of(&self, seq : i64) -> Option<(String, i32)> {
+    let entry = &self.recent[seq.rem_euclid(RECENT as i64) as usize];
+    if entry.seq != seq || entry.len > RECENT_CONTEXT {
+      return None;
+    }
+    let context = String::from_utf8_lossy(&entry.context[..entry.len]).into_owned();
+    return Some((context, entry.pid));
+  }
+}
+
+unsafe impl PGRXSharedMemory for Watermark {}
+
+pub static WATERMARK : PgLwLock<Watermark> = PgLwLock::new();
+
+static ENABLED : AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+  //  Used without shared memory.
+  static ref LOCAL_WATERMARK : Mutex<Watermark> = Mutex::new(Watermark::default());
+
+  //  Last non-blocking write of this session to each context, until
+  //  a sync covers it.
+  static ref PENDING : Mutex<HashMap<String, i64>> = Mutex::new(HashMap::new());
+}
+
+//  Last write of this session.
+static LAST_SEQ : AtomicI64 = AtomicI64::new(0);
+
+pub fn init() {
+  if unsafe { !pg_sys::process_shared_preload_libraries_in_progress } {
+    return;
+  }
+  pg_shmem_init!(WATERMARK);
+  ENABLED.store(true, Ordering::Relaxed);
+}
+
+fn with_watermark<T>(f : impl FnOnce(&mut Watermark) -> T) -> T {
+  if ENABLED.load(Ordering::Relaxed) {
+    f(&mut WATERMARK.exclusive())
+  } else {
+    f(&mut LOCAL_WATERMARK.lock().unwrap())
+  }
+}
+
+pub fn last_seq() -> i64 {
+  LAST_SEQ.load(Ordering::Relaxed)
+}
+
+fn is_blocking(payload : &[u8]) -> bool {
+  match rmp_serde::from_slice::<(String, String, bool, serde::de::IgnoredAny)>(payload) {
+    Ok((_, _, blocking, _)) => blocking,
+    Err(_)                  => false,
+  }
+}
+
+//  Called for every acknowledged request.
+pub fn acknowledged(payload : &[u8]) {
+  let (id, context) = crate::command_header(payload);
+  if !WRITES.contains(&id.as_str()) {
+    return;
+  }
+
+  let seq = with_watermark(|x| x.issue(&context));
+
+  //  Blocking writes are already applied, so there's nothing to wait for.
+  if !is_blocking(payload) {
+    LAST_SEQ.store(seq, Ordering::Relaxed);
+    PENDING.lock().unwrap().insert(context, seq);
+  }
+}
+
+//  Waits for writes to the context acknowledged so far to be applied,
+//  or for all writes with the empty context.
+pub fn sync(context : &str, timeout_msec : Option<u64>) -> Result<(), Box<dyn Error + 'static>> {
+  let issued = with_watermark(|x| x.issued);
+
+  let payload = encode_request(&Command {
+    id       : CMD_SYNC.to_string(),
+    context  : context.to_string(),
+    blocking : true,
+    payload  : rmp_serde::to_vec(&())?
+  })?;
+
+  let _ : () = crate::request(payload, timeout_msec)?;
+
+  //  The watermark covers writes to all contexts.
+  if !context.is_empty() {
+    let mut pending = PENDING.lock().unwrap();
+    if pending.get(context).map_or(false, |seq| *seq <= issued) {
+      pending.remove(context);
+    }
+    return Ok(());
+  }
+
+  let advanced = with_watermark(|x| {
+    let advanced = issued > x.applied;
+    x.applied    = x.applied.max(issued);
+    advanced
+  });
+
+  PENDING.lock().unwrap().retain(|_, seq| *seq > issued);
+
+  //  Responses cached while the writes were queued may be stale.
+  if advanced {
+    cache::invalidate();
+    shared_cache::invalidate();
+  }
+
+  return Ok(());
+}
+
+//  Returns false on timeout.
+pub fn wait_for(seq : i64, timeout_msec : Option<u64>) -> Result<bool, Box<dyn Error + 'static>> {
+  let (issued, applied, recent) = with_watermark(|x| (x.issued, x.applied, x.context_of(seq)));
+
+  if seq <= applied {
+    return Ok(true);
+  }
+  //  Without shared memory numbers of other backends are not known
+  //  here, and a sync of all contexts covers them too.
+  if seq > issued && ENABLED.load(Ordering::Relaxed) {
+    return Err(Box::from(format!("sequence number {} has not been issued", seq)));
+  }
+
+  let (context, pid) = recent.unwrap_or(("".to_string(), 0));
+
+  match sync(&context, timeout_msec) {
+    Ok(_)                                  => {},
+    Err(e) if crate::stats::is_timeout(&e) => return Ok(false),
+    Err(e)                                 => return Err(e),
+  };
+
+  //  Writes of this backend have dropped its cache already, writes of
+  //  others have not.
+  if pid != unsafe { pg_sys::MyProcPid } {
+    cache::invalidate();
+  }
+
+  return Ok(true);
+}
+
+//  Called by read functions with their `min_seq` argument.
+pub fn require(min_seq : Option<i64>) -> Result<(), Box<dyn Error + 'static>> {
+  let seq = match min_seq {
+    Some(x) => x,
+    None    => return Ok(()),
+  };
+
+  if !wait_for(seq, Some(*crate::RECV_TIMEOUT_MSEC))? {
+    return Err(Box::from(format!("timed out waiting for write {} to be applied", seq)));
+  }
+  return Ok(());
+}
+
+//  Context to sync before reading `context`, if any.
+fn read_sync_context(context : &str) -> Option<String> {
+  let applied     = with_watermark(|x| x.applied);
+  let mut pending = PENDING.lock().unwrap();
+
+  pending.retain(|_, seq| *seq > applied);
+
+  if pending.is_empty() {
+    return None;
+  }
+  if context.is_empty() || pending.contains_key("") {
+    return Some("".to_string());
+  }
+  if pending.contains_key(context) {
+    return Some(context.to_string());
+  }
+  return None;
+}
+
+//  Whether non-blocking writes of this session may not be applied yet
+//  to what a read of `context` returns.
+pub fn pending(context : &str) -> bool {
+  return read_sync_context(context).is_some();
+}
+
+//  Called before every read request.
+pub fn before_read(payload : &[u8]) -> Result<(), Box<dyn Error + 'static>> {
+  if !guc::READ_YOUR_WRITES.get() || last_seq() == 0 {
+    return Ok(());
+  }
+
+  let (id, context) = crate::command_header(payload);
+  if !cache::CACHEABLE.contains(&id.as_str()) {
+    return Ok(());
+  }
+
+  let context = match read_sync_context(&context) {
+    Some(x) => x,
+    None    => return Ok(()),
+  };
+
+  match sync(&context, Some(*crate::RECV_TIMEOUT_MSEC)) {
+    Ok(_)                                  => Ok(()),
+    Err(e) if crate::stats::is_timeout(&e) => Err(Box::from(format!("timed out waiting for writes to context \"{}\" to be applied", context))),
+    Err(e)                                 => Err(e),
+  }
+}
+
+//  ================================================================
+//
+//    SQL
+//
+//  ================================================================
+
+#[pg_extern]
+fn mr_last_seq() -> i64 {
+  last_seq()
+}
+
+#[pg_extern]
+fn mr_wait_for(
+  seq          : i64,
+  timeout_msec : default!(Option<i32>, "10000"),
+) -> Result<bool, Box<dyn Error + 'static>> {
+  wait_for(seq, timeout_msec.map(|x| x as u64))
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use crate::testing::Scope;
+
+  #[pg_test]
+  fn seq_wait_for() {
+    let t = Scope::new();
+    let x = t.context("X");
+
+    let before = super::mr_last_seq();
+
+    let _ = crate::mr_put_edge(Some(t.node("U1").as_str()), Some(t.node("U2").as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let seq = super::mr_last_seq();
+
+    //  Writes of other tests may be interleaved.
+    assert!(seq > before);
+
+    let _ = crate::mr_nodelist(Some(x.as_str()), None).unwrap();
+    assert_eq!(super::mr_last_seq(), seq);
+
+    assert_eq!(super::mr_wait_for(seq, None).unwrap(), true);
+    assert_eq!(super::mr_wait_for(seq, None).unwrap(), true);
+    assert!(super::mr_wait_for(i64::MAX, None).is_err());
+  }
+
+  #[pg_test]
+  fn seq_read_your_writes() {
+    let t = Scope::new();
+    let x = t.context("X");
+
+    Spi::run("SET meritrank.read_your_writes = on").unwrap();
+
+    let _ = crate::mr_put_edge(Some(t.node("U1").as_str()), Some(t.node("U2").as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+
+    //  No mr_sync needed.
+    assert_eq!(crate::mr_edgelist(Some(x.as_str()), None).unwrap().count(), 1);
+    //  Synced with a sync of X, which doesn't advance the watermark.
+    assert!(!super::PENDING.lock().unwrap().contains_key(&x));
+    //  So responses are cached again.
+    assert!(!super::pending(&x));
+
+    Spi::run("RESET meritrank.read_your_writes").unwrap();
+  }
+
+  #[pg_test]
+  fn seq_read_your_writes_context() {
+    use crate::mock::MockService;
+    use meritrank_service::protocol::CMD_SYNC;
+
+    //  Swaps the service and counts syncs.
+    crate::testing::exclusive();
+
+    let url     = format!("ipc:///tmp/pgmer2-mock-ryw-{}", std::process::id());
+    let service = MockService::start(&url).unwrap();
+    crate::set_service_url(Some(url));
+
+    //  Covers writes of earlier tests in this session.
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+    let syncs = service.count(CMD_SYNC);
+
+    Spi::run("SET meritrank.read_your_writes = on").unwrap();
+
+    let _ = crate::mr_put_edge(Some("U1"), Some("U2"), Some(1.0), Some("X"), Some(false), None).unwrap();
+
+    //  Writes to X can't affect Y.
+    let _ = crate::mr_edgelist(Some("Y"), None).unwrap();
+    assert_eq!(service.count(CMD_SYNC), syncs);
+
+    let _ = crate::mr_edgelist(Some("X"), None).unwrap();
+    assert_eq!(service.count(CMD_SYNC), syncs + 1);
+
+    //  Already synced.
+    let _ = crate::mr_edgelist(Some("X"), None).unwrap();
+    assert_eq!(service.count(CMD_SYNC), syncs + 1);
+
+    //  The empty context sums all contexts.
+    let _ = crate::mr_put_edge(Some("U1"), Some("U3"), Some(1.0), Some("X"), Some(false), None).unwrap();
+    let _ = crate::mr_edgelist(None, None).unwrap();
+    assert_eq!(service.count(CMD_SYNC), syncs + 2);
+
+    Spi::run("RESET meritrank.read_your_writes").unwrap();
+
+    //  Waiting for a write syncs only its context, which doesn't
+    //  advance the watermark.
+    let _ = crate::mr_put_edge(Some("U1"), Some("U4"), Some(1.0), Some("X"), Some(false), None).unwrap();
+    let seq = super::mr_last_seq();
+    assert_eq!(super::mr_wait_for(seq, None).unwrap(), true);
+    assert_eq!(service.count(CMD_SYNC), syncs + 3);
+    assert!(!super::PENDING.lock().unwrap().contains_key("X"));
+    assert!(super::with_watermark(|x| x.applied) < seq);
+
+    //  And so does a read with `min_seq`.
+    let _ = crate::mr_put_edge(Some("U1"), Some("U5"), Some(1.0), Some("X"), Some(false), None).unwrap();
+    let seq = super::mr_last_seq();
+    assert_eq!(crate::mr_edgelist(Some("X"), Some(seq)).unwrap().count(), 4);
+    assert_eq!(service.count(CMD_SYNC), syncs + 4);
+    crate::set_service_url(None);
+  }
+}
@@ -0,0 +1,382 @@
+use pgrx::*;
+use pgrx::prelude::*;
+use meritrank_service::protocol::*;
+use std::error::Error;
+use std::ffi::c_char;
+use std::mem::size_of;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::guc;
+
+//  ================================================================
+//
+//    Shared-memory score cache
+//
+//  ================================================================
+//
+//  Responses to `mr_node_score` and `mr_scores` are shared by all
+//  backends. The cache is a table of fixed-size slots, each key may
+//  live in one of `WAYS` consecutive slots after its hash. Entries
+//  are tagged with a generation which is bumped when a mutation or
+//  zerorec is acknowledged and again when `mr_sync` sees queued writes
+//  applied. A backend that did not write cannot tell when the service
+//  applies a queued write, so a score computed in between may be stored
+//  under the new generation. Entries therefore also expire after
+//  `meritrank.shared_cache_ttl`, which bounds how long such a stale
+//  score is served.
+//
+//  The size comes from `meritrank.shared_cache_size`, so shared
+//  memory is requested by hand instead of with `pg_shmem_init!`.
+
+const SHARED_CACHEABLE : &[&str] = &[
+  CMD_NODE_SCORE,
+  CMD_SCORES,
+];
+
+const WAYS      : u64   = 4;
+const SLOT_DATA : usize = 4056;
+
+const SHMEM_NAME : &[u8] = b"pgmer2 shared cache\0";
+const TRANCHE    : &[u8] = b"pgmer2_cache\0";
+
+//  Index of `AddinShmemInitLock` in `MainLWLockArray`, see
+//  `src/include/storage/lwlocknames.h` of PostgreSQL. It is a macro
+//  there, so bindgen doesn't export it, and it is 21 in all supported
+//  versions.
+const ADDIN_SHMEM_INIT_LOCK : usize = 21;
+
+#[repr(C)]
+struct Header {
+  slots      : u64,
+  generation : AtomicU64,
+  clock      : AtomicU64,
+  hits       : AtomicU64,
+  misses     : AtomicU64,
+  stores     : AtomicU64,
+}
+
+#[repr(C)]
+struct Slot {
+  hash       : u64,
+  //  Zero means the slot is empty, generations start at one.
+  generation : u64,
+  used       : AtomicU64,
+  //  Microseconds since the Unix epoch.
+  stored_at  : u64,
+  key_len    : u32,
+  value_len  : u32,
+  data       : [u8; SLOT_DATA],
+}
+
+static mut HEADER : *mut Header         = null_mut();
+static mut LOCK   : *mut pg_sys::LWLock = null_mut();
+
+#[cfg(any(feature = "pg15", feature = "pg16"))]
+static mut PREV_SHMEM_REQUEST_HOOK : pg_sys::shmem_request_hook_type = None;
+static mut PREV_SHMEM_STARTUP_HOOK : pg_sys::shmem_startup_hook_type = None;
+
+fn slot_count() -> u64 {
+  let bytes = guc::SHARED_CACHE_SIZE.get().max(0) as usize * 1024;
+  return (bytes.saturating_sub(size_of::<Header>()) / size_of::<Slot>()) as u64;
+}
+
+fn shmem_size() -> usize {
+  size_of::<Header>() + slot_count() as usize * size_of::<Slot>()
+}
+
+pub fn init() {
+  if unsafe { !pg_sys::process_shared_preload_libraries_in_progress } || slot_count() == 0 {
+    return;
+  }
+
+  unsafe {
+    #[cfg(any(feature = "pg15", feature = "pg16"))]
+    {
+      PREV_SHMEM_REQUEST_HOOK = pg_sys::shmem_request_hook;
+      pg_sys::shmem_request_hook = Some(shmem_request);
+    }
+    #[cfg(not(any(feature = "pg15", feature = "pg16")))]
+    request();
+
+    PREV_SHMEM_STARTUP_HOOK = pg_sys::shmem_startup_hook;
+    pg_sys::shmem_startup_hook = Some(shmem_startup);
+  }
+}
+
+unsafe fn request() {
+  pg_sys::RequestAddinShmemSpace(shmem_size());
+  pg_sys::RequestNamedLWLockTranche(TRANCHE.as_ptr() as *const c_char, 1);
+}
+
+#[cfg(any(feature = "pg15", feature = "pg16"))]
+#[pg_guard]
+unsafe extern "C" fn shmem_request() {
+  if let Some(prev) = PREV_SHMEM_REQUEST_HOOK {
+    prev();
+  }
+  request();
+}
+
+#[pg_guard]
+unsafe extern "C" fn shmem_startup() {
+  if let Some(prev) = PREV_SHMEM_STARTUP_HOOK {
+    prev();
+  }
+
+  let init_lock : *mut pg_sys::LWLock = &mut (*pg_sys::MainLWLockArray.add(ADDIN_SHMEM_INIT_LOCK)).lock;
+  pg_sys::LWLockAcquire(init_lock, pg_sys::LWLockMode_LW_EXCLUSIVE);
+
+  let mut found = false;
+  let header    = pg_sys::ShmemInitStruct(SHMEM_NAME.as_ptr() as *const c_char, shmem_size(), &mut found) as *mut Header;
+
+  if !found {
+    std::ptr::write_bytes(header as *mut u8, 0, shmem_size());
+    (*header).slots = slot_count();
+    (*header).generation.store(1, Ordering::Relaxed);
+  }
+
+  HEADER = header;
+  LOCK   = &mut (*pg_sys::GetNamedLWLockTranche(TRANCHE.as_ptr() as *const c_char)).lock;
+
+  pg_sys::LWLockRelease(init_lock);
+}
+
+fn header() -> Option<&'static Header> {
+  unsafe { HEADER.as_ref() }
+}
+
+unsafe fn slot(header : &Header, n : u64) -> *mut Slot {
+  let first = (header as *const Header).add(1) as *mut Slot;
+  return first.add((n % header.slots) as usize);
+}
+
+//  Holds the cache lock until dropped.
+struct Locked;
+
+impl Locked {
+  fn new(exclusive : bool) -> Locked {
+    let mode = if exclusive { pg_sys::LWLockMode_LW_EXCLUSIVE } else { pg_sys::LWLockMode_LW_SHARED };
+    unsafe { pg_sys::LWLockAcquire(LOCK, mode) };
+    return Locked;
+  }
+}
+
+impl Drop for Locked {
+  fn drop(&mut self) {
+    unsafe { pg_sys::LWLockRelease(LOCK) };
+  }
+}
+
+//  FNV-1a.
+fn hash(key : &[u8]) -> u64 {
+  key.iter().fold(0xcbf29ce484222325, |h, b| (h ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+fn now_usec() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |x| x.as_micros() as u64)
+}
+
+fn is_fresh(slot : &Slot, now : u64) -> bool {
+  let ttl = guc::SHARED_CACHE_TTL.get();
+  ttl <= 0 || now.saturating_sub(slot.stored_at) < ttl as u64 * 1000
+}
+
+fn is_cacheable(key : &[u8]) -> bool {
+  let (id, _) = crate::command_header(key);
+  SHARED_CACHEABLE.contains(&id.as_str())
+}
+
+pub fn enabled() -> bool {
+  header().is_some()
+}
+
+pub fn get(key : &[u8]) -> Option<Vec<u8>> {
+  let header = header()?;
+  if !is_cacheable(key) {
+    return None;
+  }
+
+  let hash       = hash(key);
+  let _lock      = Locked::new(false);
+  let generation = header.generation.load(Ordering::Acquire);
+  let now        = now_usec();
+
+  for n in 0..WAYS {
+    let slot = unsafe { &*slot(header, hash.wrapping_add(n)) };
+    let len  = slot.key_len as usize;
+
+    if slot.hash == hash && slot.generation == generation && &slot.data[..len] == key && is_fresh(slot, now) {
+      slot.used.store(header.clock.fetch_add(1, Ordering::Relaxed), Ordering::Relaxed);
+      header.hits.fetch_add(1, Ordering::Relaxed);
+      return Some(slot.data[len..len + slot.value_len as usize].to_vec());
+    }
+  }
+
+  header.misses.fetch_add(1, Ordering::Relaxed);
+  return None;
+}
+
+pub fn generation() -> u64 {
+  header().map_or(0, |header| header.generation.load(Ordering::Acquire))
+}
+
+pub fn put(key : &[u8], value : &[u8], generation : u64) {
+  let header = match header() {
+    Some(x) => x,
+    None    => return,
+  };
+  if !is_cacheable(key) || key.len() + value.len() > SLOT_DATA {
+    return;
+  }
+
+  let hash  = hash(key);
+  let _lock = Locked::new(true);
+
+  //  The graph has changed since the request was sent.
+  if generation != header.generation.load(Ordering::Acquire) {
+    return;
+  }
+
+  //  Take a stale slot if there is one, the least recently used otherwise.
+  let victim = (0..WAYS)
+    .map(|n| unsafe { &mut *slot(header, hash.wrapping_add(n)) })
+    .min_by_key(|slot| if slot.generation == generation { 1 + slot.used.load(Ordering::Relaxed) } else { 0 })
+    .unwrap();
+
+  victim.hash       = hash;
+  victim.generation = generation;
+  victim.stored_at  = now_usec();
+  victim.key_len    = key.len() as u32;
+  victim.value_len  = value.len() as u32;
+  victim.data[..key.len()].copy_from_slice(key);
+  victim.data[key.len()..key.len() + value.len()].copy_from_slice(value);
+  victim.used.store(header.clock.fetch_add(1, Ordering::Relaxed), Ordering::Relaxed);
+
+  header.stores.fetch_add(1, Ordering::Relaxed);
+}
+
+//  Drops all entries at once for every backend.
+pub fn invalidate() {
+  if let Some(header) = header() {
+    header.generation.fetch_add(1, Ordering::AcqRel);
+  }
+}
+
+//  ================================================================
+//
+//    SQL
+//
+//  ================================================================
+
+#[pg_extern]
+fn mr_shared_cache_stats() -> Result<
+  TableIterator<'static, (
+    name!(slots,      i64),
+    name!(entries,    i64),
+    name!(generation, i64),
+    name!(hits,       i64),
+    name!(misses,     i64),
+    name!(stores,     i64),
+  )>,
+  Box<dyn Error + 'static>,
+> {
+  let header = match header() {
+    Some(x) => x,
+    None    => return Err(Box::from("shared cache requires pgmer2 in shared_preload_libraries and meritrank.shared_cache_size")),
+  };
+
+  let _lock      = Locked::new(false);
+  let generation = header.generation.load(Ordering::Acquire);
+  let now        = now_usec();
+  let entries    = (0..header.slots)
+    .map(|n| unsafe { &*slot(header, n) })
+    .filter(|slot| slot.generation == generation && is_fresh(slot, now))
+    .count();
+
+  return Ok(TableIterator::once((
+    header.slots as i64,
+    entries      as i64,
+    generation   as i64,
+    header.hits  .load(Ordering::Relaxed) as i64,
+    header.misses.load(Ordering::Relaxed) as i64,
+    header.stores.load(Ordering::Relaxed) as i64,
+  )));
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use crate::testing::{exclusive, Scope};
+
+  fn stats() -> (i64, i64, i64) {
+    let (_, _, generation, hits, misses, _) =
+      crate::shared_cache::mr_shared_cache_stats().unwrap().next().unwrap();
+    (generation, hits, misses)
+  }
+
+  fn stores() -> i64 {
+    crate::shared_cache::mr_shared_cache_stats().unwrap().next().unwrap().5
+  }
+
+  #[pg_test]
+  fn shared_cache() {
+    exclusive();
+
+    let t = Scope::new();
+    let x = t.context("X");
+    let (u1, u2) = (t.node("U1"), t.node("U2"));
+
+    let node_score = || crate::mr_node_score(Some(u1.as_str()), Some(u2.as_str()), Some(x.as_str()), None).unwrap().count();
+
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u2.as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+
+    let (generation, hits, misses) = stats();
+
+    assert_eq!(node_score(), 1);
+    assert_eq!(node_score(), 1);
+    assert_eq!(stats(), (generation, hits + 1, misses + 1));
+
+    //  Mutations make all entries stale.
+    let _ = crate::mr_delete_edge(Some(u1.as_str()), Some(u2.as_str()), Some(x.as_str()), None).unwrap();
+    assert_eq!(stats().0, generation + 1);
+
+    let _ = node_score();
+    assert_eq!(stats(), (generation + 1, hits + 1, misses + 2));
+  }
+
+  #[pg_test]
+  fn shared_cache_queued_writes() {
+    exclusive();
+
+    let t = Scope::new();
+    let x = t.context("X");
+    let (u1, u2) = (t.node("U1"), t.node("U2"));
+
+    let node_score = || crate::mr_node_score(Some(u1.as_str()), Some(u2.as_str()), Some(x.as_str()), None).unwrap().count();
+
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u2.as_str()), Some(1.0), Some(x.as_str()), Some(false), None).unwrap();
+    let (generation, _, _) = stats();
+    let stored = stores();
+
+    //  The edge may not be applied yet, so the answer is not stored.
+    let _ = node_score();
+    assert_eq!(stores(), stored);
+
+    //  Applying queued writes makes entries stored meanwhile stale.
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+    assert!(stats().0 > generation);
+
+    assert_eq!(node_score(), 1);
+    assert_eq!(stores(), stored + 1);
+  }
+}
@@ -0,0 +1,287 @@
+use pgrx::*;
+use pgrx::prelude::*;
+use pgrx::lwlock::PgLwLock;
+use pgrx::shmem::*;
+use nng::Message;
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::SUPPORTED_COMMANDS;
+
+//  ================================================================
+//
+//    Shared memory
+//
+//  ================================================================
+
+//  Upper bounds of latency histogram buckets, in milliseconds.
+//  The implicit last bucket is +Inf.
+pub const LATENCY_BUCKETS_MSEC : [u64; 12] = [
+  1, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000,
+];
+
+pub const ERROR_CLASSES : [&str; 3] = ["timeout", "transport", "decode"];
+
+const ERROR_TIMEOUT   : usize = 0;
+const ERROR_TRANSPORT : usize = 1;
+const ERROR_DECODE    : usize = 2;
+
+#[derive(Copy, Clone, Default)]
+pub struct CommandStats {
+  pub calls      : u64,
+  pub errors     : u64,
+  pub timeouts   : u64,
+  pub total_usec : u64,
+  pub max_usec   : u64,
+  pub bytes_sent : u64,
+  pub bytes_recv : u64,
+  //  Non-cumulative counts per bucket of `LATENCY_BUCKETS_MSEC`.
+  pub buckets    : [u64; LATENCY_BUCKETS_MSEC.len()],
+}
+
+//  One slot per command from `SUPPORTED_COMMANDS`, in the same order.
+#[derive(Copy, Clone, Default)]
+pub struct RequestStats {
+  pub commands    : [CommandStats; SUPPORTED_COMMANDS.len()],
+  pub errors      : [u64; ERROR_CLASSES.len()],
+  pub dials       : u64,
+  pub dial_errors : u64,
+}
+
+unsafe impl PGRXSharedMemory for RequestStats {}
+
+pub static REQUEST_STATS : PgLwLock<RequestStats> = PgLwLock::new();
+
+//  Shared memory is only available when the extension is loaded
+//  via `shared_preload_libraries`.
+static ENABLED : AtomicBool = AtomicBool::new(false);
+
+//  Time this backend spent on requests, including the round trip to
+//  the service. Kept regardless of shared memory, for benchmarks.
+static BACKEND_REQUEST_USEC : AtomicU64 = AtomicU64::new(0);
+
+pub fn backend_request_usec() -> u64 {
+  BACKEND_REQUEST_USEC.load(Ordering::Relaxed)
+}
+
+pub fn init() {
+  if unsafe { !pg_sys::process_shared_preload_libraries_in_progress } {
+    return;
+  }
+  pg_shmem_init!(REQUEST_STATS);
+  ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+  ENABLED.load(Ordering::Relaxed)
+}
+
+fn ensure_enabled() -> Result<(), Box<dyn Error + 'static>> {
+  if !enabled() {
+    return Err(Box::from("request statistics require pgmer2 in shared_preload_libraries"));
+  }
+  return Ok(());
+}
+
+pub fn is_timeout(e : &Box<dyn Error + 'static>) -> bool {
+  matches!(e.downcast_ref::<nng::Error>(), Some(nng::Error::TimedOut))
+}
+
+pub fn record(
+  payload  : &[u8],
+  response : &Result<Message, Box<dyn Error + 'static>>,
+  elapsed  : Duration,
+) {
+  BACKEND_REQUEST_USEC.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+
+  if !enabled() {
+    return;
+  }
+
+  let (id, _) = crate::command_header(payload);
+  let index   = match SUPPORTED_COMMANDS.iter().position(|x| *x == id) {
+    Some(n) => n,
+    None    => return,
+  };
+  let usec    = elapsed.as_micros() as u64;
+
+  let mut guard = REQUEST_STATS.exclusive();
+  let stats     = &mut *guard;
+  let command   = &mut stats.commands[index];
+
+  command.calls      += 1;
+  command.total_usec += usec;
+  command.max_usec    = command.max_usec.max(usec);
+  command.bytes_sent += payload.len() as u64;
+
+  if let Some(n) = LATENCY_BUCKETS_MSEC.iter().position(|x| usec <= x * 1000) {
+    command.buckets[n] += 1;
+  }
+
+  match response {
+    Ok(msg) => command.bytes_recv += msg.len() as u64,
+    Err(e)  => {
+      command.errors += 1;
+      if is_timeout(e) {
+        command.timeouts += 1;
+        stats.errors[ERROR_TIMEOUT] += 1;
+      } else {
+        stats.errors[ERROR_TRANSPORT] += 1;
+      }
+    },
+  }
+}
+
+pub fn record_dial(ok : bool) {
+  if !enabled() {
+    return;
+  }
+
+  let mut stats = REQUEST_STATS.exclusive();
+  stats.dials += 1;
+  if !ok {
+    stats.dial_errors += 1;
+  }
+}
+
+//  Also counts as an error of the command, `payload` is the request.
+pub fn record_decode_error(payload : &[u8]) {
+  if !enabled() {
+    return;
+  }
+
+  let (id, _) = crate::command_header(payload);
+  let mut stats = REQUEST_STATS.exclusive();
+
+  stats.errors[ERROR_DECODE] += 1;
+  if let Some(n) = SUPPORTED_COMMANDS.iter().position(|x| *x == id) {
+    stats.commands[n].errors += 1;
+  }
+}
+
+pub fn snapshot() -> Result<RequestStats, Box<dyn Error + 'static>> {
+  ensure_enabled()?;
+  return Ok(*REQUEST_STATS.share());
+}
+
+//  ================================================================
+//
+//    SQL
+//
+//  ================================================================
+
+#[pg_extern]
+fn mr_stat_requests() -> Result<
+  TableIterator<'static, (
+    name!(command,         String),
+    name!(calls,           i64),
+    name!(errors,          i64),
+    name!(timeouts,        i64),
+    name!(total_time_msec, f64),
+    name!(mean_time_msec,  Option<f64>),
+    name!(max_time_msec,   f64),
+    name!(bytes_sent,      i64),
+    name!(bytes_received,  i64),
+  )>,
+  Box<dyn Error + 'static>,
+> {
+  let stats = snapshot()?;
+
+  let rows : Vec<_> =
+    SUPPORTED_COMMANDS
+      .iter()
+      .zip(stats.commands.iter())
+      .map(|(id, x)| (
+        id.to_string(),
+        x.calls    as i64,
+        x.errors   as i64,
+        x.timeouts as i64,
+        x.total_usec as f64 / 1000.0,
+        if x.calls > 0 { Some(x.total_usec as f64 / 1000.0 / x.calls as f64) } else { None },
+        x.max_usec   as f64 / 1000.0,
+        x.bytes_sent as i64,
+        x.bytes_recv as i64,
+      ))
+      .collect();
+
+  return Ok(TableIterator::new(rows.into_iter()));
+}
+
+#[pg_extern]
+pub fn mr_stat_reset() -> Result<&'static str, Box<dyn Error + 'static>> {
+  ensure_enabled()?;
+  *REQUEST_STATS.exclusive() = RequestStats::default();
+  return Ok("Ok");
+}
+
+extension_sql!(r#"
+CREATE OR REPLACE VIEW mr_stat_requests AS
+  SELECT * FROM mr_stat_requests();
+"#,
+  name     = "stat_requests_view",
+  requires = [mr_stat_requests],
+);
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use meritrank_service::protocol::*;
+
+  #[pg_test]
+  fn stat_requests() {
+    crate::testing::exclusive();
+    let _ = crate::stats::mr_stat_reset().unwrap();
+    let _ = crate::mr_nodelist(None, None).unwrap();
+    let _ = crate::mr_nodelist(None, None).unwrap();
+
+    let calls = Spi::get_one_with_args::<i64>(
+      "SELECT calls FROM mr_stat_requests WHERE command = $1",
+      vec![(PgBuiltInOids::TEXTOID.oid(), CMD_NODE_LIST.into_datum())],
+    ).unwrap().unwrap();
+
+    assert_eq!(calls, 2);
+
+    let _ = crate::stats::mr_stat_reset().unwrap();
+
+    let calls = Spi::get_one::<i64>(
+      "SELECT sum(calls)::bigint FROM mr_stat_requests",
+    ).unwrap().unwrap();
+
+    assert_eq!(calls, 0);
+  }
+
+  #[pg_test]
+  fn stat_decode_errors() {
+    use crate::mock::{Faults, MockService};
+
+    let url     = format!("ipc:///tmp/pgmer2-mock-stat-decode-{}", std::process::id());
+    let service = MockService::start(&url).unwrap();
+    crate::testing::shared();
+    crate::set_service_url(Some(url));
+    let _ = crate::mr_nodelist(None, None).unwrap();
+
+    let errors = || Spi::get_one_with_args::<i64>(
+      "SELECT errors FROM mr_stat_requests WHERE command = $1",
+      vec![(PgBuiltInOids::TEXTOID.oid(), CMD_NODE_LIST.into_datum())],
+    ).unwrap().unwrap();
+
+    let before = errors();
+
+    service.set_faults(Faults { garbage : true, ..Faults::default() });
+    assert!(crate::mr_nodelist(None, None).is_err());
+
+    //  Other tests may fail concurrently.
+    assert!(errors() > before);
+
+    service.set_faults(Faults::default());
+    crate::set_service_url(None);
+  }
+}
@@ -0,0 +1,108 @@
+use nng::*;
+#[cfg(feature = "tls")]
+use nng::options::Options;
+#[cfg(feature = "tls")]
+use nng::options::transport::tls::{CaFile, CertKeyFile, ServerName};
+use std::error::Error;
+
+#[cfg(feature = "tls")]
+use crate::guc;
+
+//  ================================================================
+//
+//    TLS transport
+//
+//  ================================================================
+
+pub const SCHEME : &str = "tls+tcp://";
+
+pub fn is_tls(url : &str) -> bool {
+  url.starts_with(SCHEME)
+}
+
+//  Dials `url`, configuring TLS from the `meritrank.tls_*` settings
+//  for `tls+tcp://` URLs. These need the `tls` feature.
+pub fn dial(client : &Socket, url : &str) -> Result<(), Box<dyn Error + 'static>> {
+  if !is_tls(url) {
+    client.dial(url)?;
+    return Ok(());
+  }
+  return dial_tls(client, url);
+}
+
+#[cfg(not(feature = "tls"))]
+fn dial_tls(_client : &Socket, _url : &str) -> Result<(), Box<dyn Error + 'static>> {
+  Err(Box::from("tls+tcp:// URLs require the connector to be built with the tls feature"))
+}
+
+#[cfg(feature = "tls")]
+fn dial_tls(client : &Socket, url : &str) -> Result<(), Box<dyn Error + 'static>> {
+
+  let dialer = DialerBuilder::new(client, url)?;
+
+  if let Some(path) = guc::string(&guc::TLS_CA_FILE) {
+    dialer.set_opt::<CaFile>(path)?;
+  }
+  if let Some(path) = guc::string(&guc::TLS_CERT_KEY_FILE) {
+    dialer.set_opt::<CertKeyFile>(path)?;
+  }
+  if let Some(name) = guc::string(&guc::TLS_SERVER_NAME) {
+    dialer.set_opt::<ServerName>(name)?;
+  }
+
+  dialer.start(false).map_err(|(_, err)| err)?;
+  return Ok(());
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(all(feature = "tls", any(test, feature = "pg_test")))]
+#[pgrx::pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use std::fs;
+  use crate::mock::{MockService, MOCK_VERSION};
+
+  //  Self-signed certificate for "localhost", valid until 2046.
+  const CERT : &str = include_str!("../fixtures/tls/cert.pem");
+  const KEY  : &str = include_str!("../fixtures/tls/key.pem");
+
+  //  Writes the certificate for NNG to load and returns paths of the
+  //  certificate and of the certificate with its key.
+  fn self_signed(dir : &str) -> (String, String) {
+    let cert = format!("{}/cert.pem", dir);
+    let both = format!("{}/both.pem", dir);
+
+    fs::write(&cert, CERT).unwrap();
+    fs::write(&both, CERT.to_string() + KEY).unwrap();
+    return (cert, both);
+  }
+
+  #[pg_test]
+  fn tls_loopback() {
+    let dir = format!("/tmp/pgmer2-tls-{}", std::process::id());
+    fs::create_dir_all(&dir).unwrap();
+    let (cert, cert_key) = self_signed(&dir);
+
+    let url = "tls+tcp://127.0.0.1:10991".to_string();
+    let _service = MockService::start_tls(&url, &cert_key).unwrap();
+    crate::set_service_url(Some(url));
+
+    Spi::run(&format!("SET meritrank.tls_ca_file = '{}'", cert)).unwrap();
+    Spi::run("SET meritrank.tls_server_name = 'localhost'").unwrap();
+    assert_eq!(crate::mr_service(), MOCK_VERSION);
+
+    //  Verification fails for a wrong server name.
+    Spi::run("SET meritrank.tls_server_name = 'example.com'").unwrap();
+    assert_ne!(crate::mr_service(), MOCK_VERSION);
+
+    Spi::run("RESET meritrank.tls_server_name").unwrap();
+    Spi::run("RESET meritrank.tls_ca_file").unwrap();
+    crate::set_service_url(None);
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
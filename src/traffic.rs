@@ -0,0 +1,266 @@
+use lazy_static::lazy_static;
+use meritrank_service::protocol::*;
+use nng::Message;
+use std::collections::VecDeque;
+use std::env::var;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, ErrorKind, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use serde::{Serialize, de::DeserializeOwned};
+
+//  ================================================================
+//
+//    Record and replay of service traffic
+//
+//  ================================================================
+//
+//  In record mode every successful request and its response are
+//  appended to a fixture file as a MessagePack pair of byte arrays.
+//  In replay mode responses are served from the fixture instead of
+//  the service, and each request must match the recorded one
+//  byte-for-byte, in order.
+//
+//  Requests are captured before signing, so fixtures do not depend
+//  on `meritrank.service_secret`.
+//
+//  Version checks are not recorded or replayed, so a fixture doesn't
+//  depend on whether the backend has checked the version already.
+//
+//  In capture mode requests are only collected and never sent, which
+//  lets tests inspect the payloads built by SQL functions.
+//
+//  Recordings of the real service are kept in `fixtures/traffic` and
+//  replayed by tests with `replayed`, which catches changes of the
+//  encoding between versions of the connector and of the protocol.
+//  Next to each recording the value returned by the test when it was
+//  recorded is kept as JSON, and tests compare the replayed values
+//  against it. Run the tests against the service with
+//  `MERITRANK_RECORD_TRAFFIC=1` to record them again.
+
+const FIXTURES : &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/traffic");
+
+enum Mode {
+  Off,
+  Record(File),
+  Replay(VecDeque<(Vec<u8>, Vec<u8>)>),
+  Capture(Vec<Vec<u8>>),
+}
+
+lazy_static! {
+  static ref MODE : Mutex<Mode> = Mutex::new(Mode::Off);
+}
+
+pub fn record(path : &str) -> Result<(), Box<dyn Error + 'static>> {
+  let file = OpenOptions::new().create(true).write(true).truncate(true).open(path)?;
+  *MODE.lock().unwrap() = Mode::Record(file);
+  return Ok(());
+}
+
+pub fn replay(path : &str) -> Result<(), Box<dyn Error + 'static>> {
+  let mut reader = BufReader::new(File::open(path)?);
+  let mut pairs  = VecDeque::new();
+
+  loop {
+    match rmp_serde::from_read::<_, (Vec<u8>, Vec<u8>)>(&mut reader) {
+      Ok(pair) => pairs.push_back(pair),
+      Err(rmp_serde::decode::Error::InvalidMarkerRead(e)) if e.kind() == ErrorKind::UnexpectedEof => break,
+      Err(e)   => return Err(e.into()),
+    }
+  }
+
+  *MODE.lock().unwrap() = Mode::Replay(pairs);
+  return Ok(());
+}
+
+pub fn stop() {
+  *MODE.lock().unwrap() = Mode::Off;
+}
+
+//  Runs `f` against the committed recording `name`. With
+//  `MERITRANK_RECORD_TRAFFIC=1` `f` talks to the service instead,
+//  and both the traffic and the returned value are recorded. Without
+//  the recording the test is skipped and `None` is returned.
+//  Names of nodes and contexts in `f` must not change between runs,
+//  see `Scope::fixed`.
+pub fn replayed<T : Serialize>(name : &str, f : impl FnOnce() -> T) -> Option<T> {
+  let path = format!("{}/{}.msgpack", FIXTURES, name);
+
+  if var("MERITRANK_RECORD_TRAFFIC").map(|x| x == "1").unwrap_or(false) {
+    assert!(var("MERITRANK_SERVICE_URL").is_ok(), "recording \"{}\" requires MERITRANK_SERVICE_URL", name);
+    std::fs::create_dir_all(FIXTURES).unwrap();
+    record(&path).unwrap();
+    let value = f();
+    stop();
+    std::fs::write(format!("{}/{}.json", FIXTURES, name), serde_json::to_vec_pretty(&value).unwrap()).unwrap();
+    return Some(value);
+  }
+
+  if !Path::new(&path).exists() {
+    pgrx::warning!("recording \"{}\" is missing, the test is skipped", name);
+    return None;
+  }
+
+  //  Connections are made to the mock, but all responses except the
+  //  version come from the file.
+  let url   = format!("ipc:///tmp/pgmer2-traffic-{}-{}", name, std::process::id());
+  let _mock = crate::mock::MockService::start(&url).unwrap();
+  crate::set_service_url(Some(url));
+  replay(&path).unwrap();
+  let value = f();
+
+  let left = match std::mem::replace(&mut *MODE.lock().unwrap(), Mode::Off) {
+    Mode::Replay(pairs) => pairs.len(),
+    _                   => 0,
+  };
+  crate::set_service_url(None);
+
+  assert_eq!(left, 0, "requests of recording \"{}\" were not replayed", name);
+  return Some(value);
+}
+
+//  Returns the value recorded together with the recording `name`.
+pub fn recorded<T : DeserializeOwned>(name : &str) -> T {
+  let path = format!("{}/{}.json", FIXTURES, name);
+  let data = std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+  return serde_json::from_slice(&data).unwrap();
+}
+
+//  Returns requests made by `f`. All of them fail without a response.
+pub fn capture(f : impl FnOnce()) -> Vec<Vec<u8>> {
+  *MODE.lock().unwrap() = Mode::Capture(vec![]);
+  f();
+  match std::mem::replace(&mut *MODE.lock().unwrap(), Mode::Off) {
+    Mode::Capture(requests) => requests,
+    _                       => vec![],
+  }
+}
+
+//  Runs `send` unless the response is replayed, and records the
+//  response when recording.
+pub fn intercept(
+  payload : &[u8],
+  send    : impl FnOnce() -> Result<Message, Box<dyn Error + 'static>>,
+) -> Result<Message, Box<dyn Error + 'static>> {
+  let mut mode = MODE.lock().unwrap();
+
+  let (id, _) = crate::command_header(payload);
+  if id == CMD_VERSION && !matches!(*mode, Mode::Capture(_)) {
+    drop(mode);
+    return send();
+  }
+
+  match &mut *mode {
+    Mode::Off => {
+      drop(mode);
+      send()
+    },
+
+    Mode::Record(file) => {
+      let response = send()?;
+      let pair     = rmp_serde::to_vec(&(payload, response.as_slice()))?;
+      file.write_all(&pair)?;
+      Ok(response)
+    },
+
+    Mode::Replay(pairs) => {
+      match pairs.pop_front() {
+        Some((request, response)) if request == payload => Ok(Message::from(response.as_slice())),
+        Some(_) => Err(Box::from("request does not match the recorded traffic")),
+        None    => Err(Box::from("no more recorded traffic to replay")),
+      }
+    },
+
+    Mode::Capture(requests) => {
+      requests.push(payload.to_vec());
+      Err(Box::from("request captured"))
+    },
+  }
+}
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use crate::testing::Scope;
+
+  fn scores(t : &Scope) -> Vec<(String, String, f64)> {
+    crate::mr_scores(
+      Some(t.node("U1").as_str()),
+      Some(false),
+      Some(t.context("X").as_str()),
+      Some("U"),
+      None, None,
+      None, None,
+      None, None, None
+    ).unwrap()
+      .map(|x| (
+        x.get_by_name("src")  .unwrap().unwrap(),
+        x.get_by_name("dst")  .unwrap().unwrap(),
+        x.get_by_name("score").unwrap().unwrap(),
+      ))
+      .collect()
+  }
+
+  #[pg_test]
+  fn record_and_replay() {
+    let path = format!("/tmp/pgmer2-traffic-{}.msgpack", std::process::id());
+    let t    = Scope::new();
+    let x    = t.context("X");
+    let (u1, u2, u3) = (t.node("U1"), t.node("U2"), t.node("U3"));
+
+    super::record(&path).unwrap();
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u2.as_str()), Some(2.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u3.as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+    let recorded = scores(&t);
+    super::stop();
+
+    //  Nothing is listening there, so all responses come from the file.
+    crate::set_service_url(Some("ipc:///tmp/pgmer2-traffic-nowhere".to_string()));
+    super::replay(&path).unwrap();
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u2.as_str()), Some(2.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u3.as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_sync(Some(1000)).unwrap();
+    let replayed = scores(&t);
+
+    //  Any change of the request payload is caught.
+    assert!(crate::mr_nodelist(Some(x.as_str()), None).is_err());
+
+    super::stop();
+    crate::set_service_url(None);
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(recorded.len(), 3);
+    assert_eq!(recorded, replayed);
+  }
+
+  #[pg_test]
+  fn replay_mismatch() {
+    let path = format!("/tmp/pgmer2-traffic-mismatch-{}.msgpack", std::process::id());
+    let t    = Scope::new();
+    let x    = t.context("X");
+    let (u1, u2) = (t.node("U1"), t.node("U2"));
+
+    //  Pass the version check, so only the edge is recorded.
+    let _ = crate::mr_nodelist(None, None).unwrap();
+
+    super::record(&path).unwrap();
+    let _ = crate::mr_put_edge(Some(u1.as_str()), Some(u2.as_str()), Some(2.0), Some(x.as_str()), None, None).unwrap();
+    super::stop();
+
+    super::replay(&path).unwrap();
+    let res = crate::mr_put_edge(Some(u1.as_str()), Some(u2.as_str()), Some(3.0), Some(x.as_str()), None, None);
+    super::stop();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(res.is_err());
+  }
+}
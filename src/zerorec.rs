@@ -0,0 +1,760 @@
+use pgrx::*;
+use pgrx::prelude::*;
+use pgrx::bgworkers::*;
+use pgrx::atomics::PgAtomic;
+use pgrx::lwlock::PgLwLock;
+use pgrx::shmem::*;
+use meritrank_service::protocol::*;
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::guc;
+
+//  ================================================================
+//
+//    Zero recalculation jobs
+//
+//  ================================================================
+//
+//  Every zero recalculation started with `mr_zerorec_start`, or run
+//  by a blocking `mr_zerorec`, takes a job slot in shared memory.
+//  Started jobs run in a dynamic background worker, so the calling
+//  backend is free right away.
+//
+//  The service doesn't report progress, so it is estimated from the
+//  duration of the last successful run.
+//
+//  A job is only finished by the process running it. A process that
+//  exits or errors out in the middle marks its jobs failed, and a
+//  worker that never starts fails its job right away, so no slot is
+//  left active forever.
+
+const MAX_JOBS  : usize = 16;
+const ERROR_LEN : usize = 256;
+
+pub const DEFAULT_TIMEOUT_MSEC : u64 = 6000000;
+
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
+pub enum State {
+  #[default]
+  Free,
+  Queued,
+  Running,
+  Succeeded,
+  Failed,
+}
+
+impl State {
+  fn name(&self) -> &'static str {
+    match self {
+      State::Free      => "free",
+      State::Queued    => "queued",
+      State::Running   => "running",
+      State::Succeeded => "succeeded",
+      State::Failed    => "failed",
+    }
+  }
+
+  fn active(&self) -> bool {
+    matches!(self, State::Queued | State::Running)
+  }
+}
+
+#[derive(Copy, Clone)]
+pub struct Job {
+  pub id           : i64,
+  pub state        : State,
+  //  Run in the background worker or by a blocking `mr_zerorec`.
+  pub background   : bool,
+  pub pid          : i32,
+  pub timeout_msec : u64,
+  pub started_at   : pg_sys::TimestampTz,
+  pub finished_at  : pg_sys::TimestampTz,
+  pub error_len    : usize,
+  pub error        : [u8; ERROR_LEN],
+}
+
+impl Default for Job {
+  fn default() -> Job {
+    Job {
+      id           : 0,
+      state        : State::Free,
+      background   : false,
+      pid          : 0,
+      timeout_msec : 0,
+      started_at   : 0,
+      finished_at  : 0,
+      error_len    : 0,
+      error        : [0; ERROR_LEN],
+    }
+  }
+}
+
+impl Job {
+  fn error(&self) -> Option<String> {
+    match self.error_len {
+      0 => None,
+      n => Some(String::from_utf8_lossy(&self.error[..n]).into_owned()),
+    }
+  }
+
+  fn set_error(&mut self, message : &str) {
+    let mut n = message.len().min(ERROR_LEN);
+    while !message.is_char_boundary(n) {
+      n -= 1;
+    }
+    self.error[..n].copy_from_slice(&message.as_bytes()[..n]);
+    self.error_len = n;
+  }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
+pub enum Trigger {
+  #[default]
+  None,
+  Interval,
+  Mutations,
+}
+
+impl Trigger {
+  fn name(&self) -> Option<&'static str> {
+    match self {
+      Trigger::None      => None,
+      Trigger::Interval  => Some("interval"),
+      Trigger::Mutations => Some("mutations"),
+    }
+  }
+}
+
+//  State of the scheduled recalculation worker.
+#[derive(Copy, Clone, Default)]
+pub struct Schedule {
+  //  Last scheduled run, or the last skipped one.
+  pub last         : Job,
+  pub last_trigger : Trigger,
+  pub last_skipped : bool,
+  pub runs         : u64,
+  pub failures     : u64,
+  pub skips        : u64,
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct Jobs {
+  pub next_id          : i64,
+  pub jobs             : [Job; MAX_JOBS],
+  //  Duration of the last successful run, to estimate progress.
+  pub last_duration_us : i64,
+  pub schedule         : Schedule,
+}
+
+unsafe impl PGRXSharedMemory for Jobs {}
+
+pub static JOBS : PgLwLock<Jobs> = PgLwLock::new();
+
+//  Mutations since the last recalculation of any kind. Counted on
+//  every write, so kept apart from `JOBS` to avoid taking its lock.
+pub static MUTATION_COUNT : PgAtomic<AtomicU64> = PgAtomic::new();
+
+static ENABLED : AtomicBool = AtomicBool::new(false);
+
+static EXIT_HOOK : AtomicBool = AtomicBool::new(false);
+
+pub fn init() {
+  if unsafe { !pg_sys::process_shared_preload_libraries_in_progress } {
+    return;
+  }
+  pg_shmem_init!(JOBS);
+  pg_shmem_init!(MUTATION_COUNT);
+  ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+  ENABLED.load(Ordering::Relaxed)
+}
+
+fn now() -> pg_sys::TimestampTz {
+  unsafe { pg_sys::GetCurrentTimestamp() }
+}
+
+fn my_pid() -> i32 {
+  unsafe { pg_sys::MyProcPid }
+}
+
+//  Takes a free slot, or the one of the oldest finished job.
+//  `None` when all slots are busy.
+fn allocate(jobs : &mut Jobs, background : bool, timeout_msec : u64) -> Option<(usize, i64)> {
+  let slot = (0..MAX_JOBS)
+    .filter(|n| !jobs.jobs[*n].state.active())
+    .min_by_key(|n| (jobs.jobs[*n].state != State::Free, jobs.jobs[*n].id))?;
+
+  jobs.next_id += 1;
+  let id        = jobs.next_id;
+
+  jobs.jobs[slot] = Job {
+    id,
+    state        : if background { State::Queued } else { State::Running },
+    background,
+    pid          : if background { 0 } else { my_pid() },
+    timeout_msec,
+    started_at   : now(),
+    ..Job::default()
+  };
+
+  //  The recalculation covers all mutations so far.
+  MUTATION_COUNT.get().store(0, Ordering::Relaxed);
+
+  return Some((slot, id));
+}
+
+//  Only one background job runs at a time, the id of the one in
+//  progress is returned as an error. Blocking runs are not tracked
+//  when all slots are busy.
+fn begin(background : bool, timeout_msec : u64) -> Option<Result<(usize, i64), i64>> {
+  let mut jobs = JOBS.exclusive();
+
+  if background {
+    if let Some(job) = jobs.jobs.iter().find(|x| x.background && x.state.active()) {
+      return Some(Err(job.id));
+    }
+  }
+
+  allocate(&mut jobs, background, timeout_msec).map(Ok)
+}
+
+//  Fails the active jobs of the given process.
+fn abandon(pid : i32, message : &str) {
+  let mut jobs = JOBS.exclusive();
+  let finished = now();
+
+  for job in jobs.jobs.iter_mut().filter(|x| x.state.active() && x.pid == pid) {
+    job.state       = State::Failed;
+    job.finished_at = finished;
+    job.set_error(message);
+  }
+}
+
+#[pg_guard]
+extern "C" fn abandon_on_exit(_code : i32, _arg : pg_sys::Datum) {
+  //  The process may exit with the lock held.
+  unsafe { pg_sys::LWLockReleaseAll() };
+  abandon(my_pid(), "the process exited before the job finished");
+}
+
+fn register_exit_hook() {
+  if !EXIT_HOOK.swap(true, Ordering::Relaxed) {
+    unsafe { pg_sys::on_proc_exit(Some(abandon_on_exit), pg_sys::Datum::from(0)) };
+  }
+}
+
+//  Fails the job if the run is interrupted by an error.
+struct Tracked {
+  slot     : usize,
+  finished : bool,
+}
+
+impl Tracked {
+  fn finish(mut self, result : &Result<(), Box<dyn Error + 'static>>) -> Job {
+    self.finished = true;
+    finish(self.slot, result)
+  }
+}
+
+impl Drop for Tracked {
+  fn drop(&mut self) {
+    if !self.finished {
+      let _ = finish(self.slot, &Err(Box::from("the job was interrupted")));
+    }
+  }
+}
+
+fn set_running(slot : usize) {
+  register_exit_hook();
+
+  let mut jobs = JOBS.exclusive();
+  let job      = &mut jobs.jobs[slot];
+  job.state    = State::Running;
+  job.pid      = my_pid();
+}
+
+fn finish(slot : usize, result : &Result<(), Box<dyn Error + 'static>>) -> Job {
+  let mut jobs = JOBS.exclusive();
+  let finished = now();
+  let started  = jobs.jobs[slot].started_at;
+
+  match result {
+    Ok(_)  => {
+      jobs.jobs[slot].state = State::Succeeded;
+      jobs.last_duration_us = finished - started;
+    },
+    Err(e) => {
+      jobs.jobs[slot].state = State::Failed;
+      jobs.jobs[slot].set_error(&e.to_string());
+    },
+  }
+  jobs.jobs[slot].finished_at = finished;
+  return jobs.jobs[slot];
+}
+
+pub fn recalculate(timeout_msec : Option<u64>) -> Result<(), Box<dyn Error + 'static>> {
+  let payload = encode_request(&Command {
+    id       : CMD_RECALCULATE_ZERO.to_string(),
+    context  : "".to_string(),
+    blocking : true,
+    payload  : rmp_serde::to_vec(&())?
+  })?;
+
+  crate::request(payload, timeout_msec)
+}
+
+//  Runs a blocking recalculation in this backend, tracked as a job
+//  when shared memory is available.
+pub fn run_blocking(timeout_msec : Option<u64>) -> Result<(), Box<dyn Error + 'static>> {
+  if !enabled() {
+    return recalculate(timeout_msec);
+  }
+
+  let slot = match begin(false, timeout_msec.unwrap_or(0)) {
+    Some(Ok((slot, _))) => slot,
+    _                   => return recalculate(timeout_msec),
+  };
+
+  register_exit_hook();
+
+  let tracked = Tracked { slot, finished : false };
+  let result  = recalculate(timeout_msec);
+  let _       = tracked.finish(&result);
+  return result;
+}
+
+//  Starts a recalculation in a background worker. Returns the job id,
+//  or the id of the job already in progress.
+pub fn start(timeout_msec : u64) -> Result<i64, Box<dyn Error + 'static>> {
+  if !enabled() {
+    return Err(Box::from("zero recalculation jobs require pgmer2 in shared_preload_libraries"));
+  }
+
+  let (slot, id) = match begin(true, timeout_msec) {
+    Some(Ok(x))   => x,
+    Some(Err(id)) => return Ok(id),
+    None          => return Err(Box::from("too many zero recalculations in progress")),
+  };
+
+  let worker = BackgroundWorkerBuilder::new("pgmer2 zerorec")
+    .set_function("mr_zerorec_worker_main")
+    .set_library("pgmer2")
+    .enable_shmem_access(None)
+    .set_argument((slot as i32).into_datum())
+    .set_notify_pid(my_pid())
+    .load_dynamic();
+
+  let worker = match worker {
+    Ok(x)  => x,
+    Err(e) => {
+      let _ = finish(slot, &Err(Box::from(format!("failed to start the background worker: {:?}", e))));
+      return Err(Box::from("failed to start the background worker, check max_worker_processes"));
+    },
+  };
+
+  //  Otherwise the job would stay queued if the postmaster gives up
+  //  on the worker.
+  if let Err(status) = worker.wait_for_startup() {
+    let _ = finish(slot, &Err(Box::from(format!("the background worker did not start: {:?}", status))));
+    return Err(Box::from("the background worker did not start"));
+  }
+
+  return Ok(id);
+}
+
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn mr_zerorec_worker_main(arg : pg_sys::Datum) {
+  BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGTERM);
+
+  let slot    = unsafe { i32::from_datum(arg, false) }.unwrap() as usize;
+  let timeout = JOBS.share().jobs[slot].timeout_msec;
+
+  set_running(slot);
+  let tracked = Tracked { slot, finished : false };
+  let result  = recalculate(Some(timeout));
+  let _       = tracked.finish(&result);
+}
+
+//  ================================================================
+//
+//    Scheduled recalculation
+//
+//  ================================================================
+//
+//  With `meritrank.zerorec_interval` or `meritrank.zerorec_mutations`
+//  set, a background worker recalculates on the interval, or once the
+//  connector has seen that many mutations since the last recalculation.
+//  A run is skipped when any recalculation is already in progress.
+
+const MUTATIONS : &[&str] = &[
+  CMD_PUT_EDGE,
+  CMD_DELETE_EDGE,
+  CMD_DELETE_NODE,
+];
+
+pub fn init_schedule() {
+  if unsafe { !pg_sys::process_shared_preload_libraries_in_progress } {
+    return;
+  }
+  if guc::ZEROREC_INTERVAL.get() <= 0 && guc::ZEROREC_MUTATIONS.get() <= 0 {
+    return;
+  }
+
+  BackgroundWorkerBuilder::new("pgmer2 zerorec scheduler")
+    .set_function("mr_zerorec_scheduler_main")
+    .set_library("pgmer2")
+    .enable_shmem_access(None)
+    .set_restart_time(Some(Duration::from_secs(10)))
+    .load();
+}
+
+pub fn count_mutation(payload : &[u8]) {
+  if !enabled() {
+    return;
+  }
+
+  let (id, _) = crate::command_header(payload);
+  if MUTATIONS.contains(&id.as_str()) {
+    MUTATION_COUNT.get().fetch_add(1, Ordering::Relaxed);
+  }
+}
+
+fn trigger(since : pg_sys::TimestampTz) -> Trigger {
+  let interval  = guc::ZEROREC_INTERVAL.get()  as i64;
+  let mutations = guc::ZEROREC_MUTATIONS.get() as i64;
+
+  if mutations > 0 && MUTATION_COUNT.get().load(Ordering::Relaxed) >= mutations as u64 {
+    return Trigger::Mutations;
+  }
+  if interval > 0 && now() - since >= interval * 1000000 {
+    return Trigger::Interval;
+  }
+  return Trigger::None;
+}
+
+//  Runs a scheduled recalculation in this process, or records a skip.
+fn run_scheduled(trigger : Trigger) {
+  let slot = {
+    let mut jobs = JOBS.exclusive();
+
+    jobs.schedule.last_trigger = trigger;
+
+    let slot = match jobs.jobs.iter().any(|x| x.state.active()) {
+      true  => None,
+      false => allocate(&mut jobs, true, DEFAULT_TIMEOUT_MSEC),
+    };
+
+    if slot.is_none() {
+      jobs.schedule.skips       += 1;
+      jobs.schedule.last_skipped = true;
+      jobs.schedule.last         = Job { started_at : now(), ..Job::default() };
+      return;
+    }
+    slot.map(|(slot, _)| slot).unwrap()
+  };
+
+  set_running(slot);
+  let tracked = Tracked { slot, finished : false };
+  let result  = recalculate(Some(DEFAULT_TIMEOUT_MSEC));
+  let job     = tracked.finish(&result);
+
+  let mut jobs = JOBS.exclusive();
+  let schedule = &mut jobs.schedule;
+
+  schedule.runs        += 1;
+  schedule.failures    += if result.is_err() { 1 } else { 0 };
+  schedule.last         = job;
+  schedule.last_skipped = false;
+}
+
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn mr_zerorec_scheduler_main(_arg : pg_sys::Datum) {
+  BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGHUP | SignalWakeFlags::SIGTERM);
+
+  let mut since = now();
+
+  while BackgroundWorker::wait_latch(Some(Duration::from_secs(1))) {
+    if BackgroundWorker::sighup_received() {
+      unsafe { pg_sys::ProcessConfigFile(pg_sys::GucContext_PGC_SIGHUP) };
+    }
+
+    match trigger(since) {
+      Trigger::None => {},
+      x             => {
+        run_scheduled(x);
+        since = now();
+      },
+    }
+  }
+}
+
+//  ================================================================
+//
+//    SQL
+//
+//  ================================================================
+
+type Row = (
+  i64,
+  String,
+  bool,
+  Option<i32>,
+  Option<TimestampWithTimeZone>,
+  Option<TimestampWithTimeZone>,
+  Option<f64>,
+  Option<f64>,
+  Option<String>,
+);
+
+fn timestamp(x : pg_sys::TimestampTz) -> Option<TimestampWithTimeZone> {
+  if x == 0 { None } else { TimestampWithTimeZone::try_from(x).ok() }
+}
+
+fn row(job : &Job, last_duration_us : i64) -> Row {
+  let end     = if job.state.active() { now() } else { job.finished_at };
+  let elapsed = end - job.started_at;
+
+  let progress = match job.state {
+    State::Succeeded                           => Some(1.0),
+    State::Running if last_duration_us > 0     => Some((elapsed as f64 / last_duration_us as f64).min(0.99)),
+    _                                          => None,
+  };
+
+  (
+    job.id,
+    job.state.name().to_string(),
+    job.background,
+    if job.pid != 0 { Some(job.pid) } else { None },
+    timestamp(job.started_at),
+    timestamp(job.finished_at),
+    Some(elapsed as f64 / 1000.0),
+    progress,
+    job.error(),
+  )
+}
+
+#[pg_extern]
+fn mr_zerorec_start(
+  timeout_msec : default!(Option<i32>, "6000000"),
+) -> Result<i64, Box<dyn Error + 'static>> {
+  let timeout_msec = timeout_msec.map_or(DEFAULT_TIMEOUT_MSEC, |x| x as u64);
+  start(timeout_msec)
+}
+
+#[pg_extern]
+fn mr_zerorec_status(
+  job : i64,
+) -> Result<
+  TableIterator<'static, (
+    name!(job,          i64),
+    name!(state,        String),
+    name!(background,   bool),
+    name!(pid,          Option<i32>),
+    name!(started_at,   Option<TimestampWithTimeZone>),
+    name!(finished_at,  Option<TimestampWithTimeZone>),
+    name!(elapsed_msec, Option<f64>),
+    name!(progress,     Option<f64>),
+    name!(error,        Option<String>),
+  )>,
+  Box<dyn Error + 'static>,
+> {
+  if !enabled() {
+    return Err(Box::from("zero recalculation jobs require pgmer2 in shared_preload_libraries"));
+  }
+
+  let jobs = *JOBS.share();
+
+  match jobs.jobs.iter().find(|x| x.state != State::Free && x.id == job) {
+    Some(x) => Ok(TableIterator::once(row(x, jobs.last_duration_us))),
+    None    => Err(Box::from(format!("zero recalculation job {} not found", job))),
+  }
+}
+
+//  Jobs in progress, like `pg_stat_progress_*` views.
+#[pg_extern]
+fn mr_stat_progress_zerorec() -> Result<
+  TableIterator<'static, (
+    name!(job,          i64),
+    name!(state,        String),
+    name!(background,   bool),
+    name!(pid,          Option<i32>),
+    name!(started_at,   Option<TimestampWithTimeZone>),
+    name!(finished_at,  Option<TimestampWithTimeZone>),
+    name!(elapsed_msec, Option<f64>),
+    name!(progress,     Option<f64>),
+    name!(error,        Option<String>),
+  )>,
+  Box<dyn Error + 'static>,
+> {
+  if !enabled() {
+    return Err(Box::from("zero recalculation jobs require pgmer2 in shared_preload_libraries"));
+  }
+
+  let jobs = *JOBS.share();
+
+  let rows : Vec<_> =
+    jobs.jobs
+      .iter()
+      .filter(|x| x.state.active())
+      .map(|x| row(x, jobs.last_duration_us))
+      .collect();
+
+  return Ok(TableIterator::new(rows.into_iter()));
+}
+
+#[pg_extern]
+fn mr_stat_zerorec_schedule() -> Result<
+  TableIterator<'static, (
+    name!(last_run_at,        Option<TimestampWithTimeZone>),
+    name!(last_duration_msec, Option<f64>),
+    name!(last_trigger,       Option<String>),
+    name!(last_outcome,       Option<String>),
+    name!(last_error,         Option<String>),
+    name!(runs,               i64),
+    name!(failures,           i64),
+    name!(skips,              i64),
+    name!(pending_mutations,  i64),
+  )>,
+  Box<dyn Error + 'static>,
+> {
+  if !enabled() {
+    return Err(Box::from("zero recalculation jobs require pgmer2 in shared_preload_libraries"));
+  }
+
+  let schedule = JOBS.share().schedule;
+  let last     = &schedule.last;
+
+  let outcome = match (schedule.last_skipped, last.state) {
+    (true, _)        => Some("skipped"),
+    (_, State::Free) => None,
+    (_, x)           => Some(x.name()),
+  };
+
+  return Ok(TableIterator::once((
+    timestamp(last.started_at),
+    if last.finished_at != 0 { Some((last.finished_at - last.started_at) as f64 / 1000.0) } else { None },
+    schedule.last_trigger.name().map(|x| x.to_string()),
+    outcome.map(|x| x.to_string()),
+    last.error(),
+    schedule.runs      as i64,
+    schedule.failures  as i64,
+    schedule.skips     as i64,
+    MUTATION_COUNT.get().load(Ordering::Relaxed) as i64,
+  )));
+}
+
+extension_sql!(r#"
+CREATE OR REPLACE VIEW mr_stat_zerorec_schedule AS
+  SELECT * FROM mr_stat_zerorec_schedule();
+"#,
+  name     = "stat_zerorec_schedule_view",
+  requires = [mr_stat_zerorec_schedule],
+);
+
+extension_sql!(r#"
+CREATE OR REPLACE VIEW mr_stat_progress_zerorec AS
+  SELECT * FROM mr_stat_progress_zerorec();
+"#,
+  name     = "stat_progress_zerorec_view",
+  requires = [mr_stat_progress_zerorec],
+);
+
+//  ================================================================
+//
+//    Tests
+//
+//  ================================================================
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+  use pgrx::prelude::*;
+  use std::time::{Duration, Instant};
+
+  fn state(job : i64) -> (String, Option<f64>) {
+    let (_, state, _, _, _, _, _, progress, _) =
+      crate::zerorec::mr_zerorec_status(job).unwrap().next().unwrap();
+    (state, progress)
+  }
+
+  #[pg_test]
+  fn zerorec_job() {
+    crate::testing::exclusive();
+
+    let job = crate::zerorec::mr_zerorec_start(None).unwrap();
+    let end = Instant::now() + Duration::from_secs(10);
+
+    while state(job).0 != "succeeded" {
+      assert!(Instant::now() < end, "job {} is still {}", job, state(job).0);
+      std::thread::sleep(Duration::from_millis(50));
+    }
+
+    assert_eq!(state(job).1, Some(1.0));
+    assert!(crate::zerorec::mr_zerorec_status(job + 1000).is_err());
+
+    //  Blocking runs are tracked too.
+    let _ = crate::mr_zerorec(Some(true), None).unwrap();
+    assert_eq!(state(job + 1).0, "succeeded");
+    assert_eq!(crate::zerorec::mr_stat_progress_zerorec().unwrap().count(), 0);
+  }
+
+  fn schedule() -> (Option<String>, Option<String>, i64, i64, i64) {
+    let (_, _, trigger, outcome, _, runs, _, skips, mutations) =
+      crate::zerorec::mr_stat_zerorec_schedule().unwrap().next().unwrap();
+    (trigger, outcome, runs, skips, mutations)
+  }
+
+  #[pg_test]
+  fn zerorec_schedule() {
+    crate::testing::exclusive();
+
+    let t = crate::testing::Scope::new();
+    let x = t.context("X");
+
+    let _ = crate::mr_zerorec(Some(true), None).unwrap();
+    let _ = crate::mr_put_edge(Some(t.node("U1").as_str()), Some(t.node("U2").as_str()), Some(1.0), Some(x.as_str()), None, None).unwrap();
+    let _ = crate::mr_delete_edge(Some(t.node("U1").as_str()), Some(t.node("U2").as_str()), Some(x.as_str()), None).unwrap();
+
+    let (_, _, runs, skips, mutations) = schedule();
+    assert_eq!(mutations, 2);
+
+    super::run_scheduled(super::Trigger::Mutations);
+
+    assert_eq!(schedule(), (Some("mutations".to_string()), Some("succeeded".to_string()), runs + 1, skips, 0));
+
+    //  Skipped while another recalculation is in progress.
+    let (slot, _) = super::begin(false, 0).unwrap().unwrap();
+    super::run_scheduled(super::Trigger::Interval);
+    let _ = super::finish(slot, &Ok(()));
+
+    assert_eq!(schedule(), (Some("interval".to_string()), Some("skipped".to_string()), runs + 1, skips + 1, 0));
+  }
+
+  #[pg_test]
+  fn zerorec_abandoned() {
+    crate::testing::exclusive();
+
+    let (slot, id) = super::begin(true, 0).unwrap().unwrap();
+    super::set_running(slot);
+
+    //  As if the process running the job had exited.
+    super::abandon(super::my_pid(), "exited");
+
+    assert_eq!(state(id).0, "failed");
+    assert_eq!(crate::zerorec::mr_stat_progress_zerorec().unwrap().count(), 0);
+
+    //  A new background job can start.
+    let (slot, next) = super::begin(true, 0).unwrap().unwrap();
+    assert!(next > id);
+
+    //  An interrupted run fails its job too.
+    drop(super::Tracked { slot, finished : false });
+    assert_eq!(state(next).0, "failed");
+  }
+}